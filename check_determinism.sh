@@ -0,0 +1,35 @@
+#!/bin/bash
+# 2026.10.18 再現性の確認
+# 同じ設定・シードでスレッド数だけを変えて実行し、college{NN}.csv、student{NN}.csv、
+# history.json、history.csvがバイト単位で同一であることを確認する。
+#   ./check_determinism.sh configS1.toml [threads...]
+config=${1:-configS1.toml}
+shift
+threads=${@:-1 2 4}
+work=$(mktemp -d)
+
+cargo build --release || exit 1
+for t in $threads; do
+    for h in json csv; do
+        mkdir -p $work/t$t/$h
+        ./target/release/examsim --threads $t -l -s 1 \
+            --set output_dir_base=\"$work/t$t/$h\" --set history_format=$h $config 2> /dev/null || exit 1
+    done
+done
+
+status=0
+ref=""
+for t in $threads; do
+    # 出力先Dir名の日時とrun.jsonは実行毎に変わるので比較しない
+    sum=$(for h in json csv; do (cd $work/t$t/$h/s*/ && md5sum *.csv history.json 2> /dev/null); done)
+    if [ -z "$ref" ]; then
+        ref=$sum
+    elif [ "$sum" != "$ref" ]; then
+        echo "NG threads=$t"
+        diff <(echo "$ref") <(echo "$sum")
+        status=1
+    fi
+done
+[ $status -eq 0 ] && echo "OK threads=$threads"
+rm -rf $work
+exit $status
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::college::CollegeResult;
+use crate::geo;
+
+// 2026.10.18 大学区分別の集計結果 1行 = エポック x 集計区分 x 区分値
+//   group: all（全体）/ institute（設置区分）/ urban（都市区分）/ pref（都道府県）/ own_scale（大学規模 L,M,S）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateResult{
+    pub epoch: i32, //エポック数
+    pub group: String, //集計区分
+    pub key: String, //区分値
+    pub label: String, //区分値の表記
+    pub colleges: i32, //大学数
+    pub enroll: u32, //入学定員数合計
+    pub apply_count: i32, //受験者数合計
+    pub admissons: i32, //入学者数合計
+    #[serde(deserialize_with = "crate::sink::f64_or_nan")]
+    pub fill_rate: f64, //入学定員充足率 入学者数合計÷入学定員数合計
+    #[serde(deserialize_with = "crate::sink::f64_or_nan")]
+    pub new_deviation: f64, //入学者偏差値平均（入学者数で加重）
+    pub fee_revenue: u64, //入学金収入合計（円）
+    pub forfeited_fees: u64, //うち入学金納付後辞退者の入学金合計
+    pub tuition_revenue: u64, //授業料収入合計
+}
+
+// 集計中の合計値
+#[derive(Default)]
+struct Totals{
+    colleges: i32,
+    enroll: u32,
+    apply_count: i32,
+    admissons: i32,
+    dev_sum: f64, //入学者偏差値平均 x 入学者数 の合計
+    fee_revenue: u64,
+    forfeited_fees: u64,
+    tuition_revenue: u64,
+}
+
+const GROUPS: [&str; 5] = ["all", "institute", "urban", "pref", "own_scale"];
+
+// 大学規模の表記。College::college_scaleの 0:大 1:中 2:小
+pub fn scale_label(own_scale: usize) -> &'static str{
+    match own_scale {
+        0 => "L",
+        1 => "M",
+        _ => "S",
+    }
+}
+
+fn institute_label(institute: u8) -> &'static str{
+    match institute {
+        1 => "国立",
+        2 => "公立",
+        _ => "私立",
+    }
+}
+
+// 区分値と表記
+fn group_key(group: &str, c: &CollegeResult) -> (String, String){
+    match group {
+        "institute" => (c.institute.to_string(), institute_label(c.institute).to_string()),
+        "urban" => (c.urban.clone(), c.urban.clone()),
+        "pref" => (format!("{:02}", c.pref), geo::pref_name(c.pref).to_string()),
+        "own_scale" => (scale_label(c.own_scale).to_string(), scale_label(c.own_scale).to_string()),
+        _ => ("all".to_string(), "全体".to_string()),
+    }
+}
+
+// 1エポック分の大学集計結果を区分別に集計
+pub fn aggregate(epoch: i32, college_results: &[CollegeResult]) -> Vec<AggregateResult>{
+    let mut results: Vec<AggregateResult> = Vec::new();
+    for group in GROUPS.iter() {
+        let mut totals: BTreeMap<(String, String), Totals> = BTreeMap::new();
+        for c in college_results {
+            let t = totals.entry(group_key(group, c)).or_default();
+            t.colleges += 1;
+            t.enroll += c.enroll;
+            t.apply_count += c.apply_count;
+            t.admissons += c.admissons;
+            t.fee_revenue += c.fee_revenue;
+            t.forfeited_fees += c.forfeited_fees;
+            t.tuition_revenue += c.tuition_revenue;
+            if c.admissons > 0 {
+                t.dev_sum += c.new_deviation * c.admissons as f64;
+            }
+        }
+        for ((key, label), t) in totals {
+            results.push(AggregateResult{
+                epoch,
+                group: group.to_string(),
+                key,
+                label,
+                colleges: t.colleges,
+                enroll: t.enroll,
+                apply_count: t.apply_count,
+                admissons: t.admissons,
+                fill_rate: t.admissons as f64 / t.enroll as f64,
+                new_deviation: t.dev_sum / t.admissons as f64,
+                fee_revenue: t.fee_revenue,
+                forfeited_fees: t.forfeited_fees,
+                tuition_revenue: t.tuition_revenue,
+            });
+        }
+    }
+    results
+}
@@ -0,0 +1,95 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::college::College;
+use crate::config::Config;
+use crate::student::Ronin;
+
+// 2026.10.18 チェックポイント
+// エポック終了毎に出力先Dirのcheckpoint.binへ保存し、--resume で続きから実行する。
+// 受験生の乱数はrandom_seedとエポックから決まるので、乱数の位置はシードと次エポックで表す。
+// 偏差値にNaNが入りうるのでJSONではなくbincodeで保存する。
+pub const CHECKPOINT_FILE: &str = "checkpoint.bin";
+
+// チェックポイントの形式。ファイル先頭に識別子とともに書く。
+// 大学エージェント・浪人生など保存する構造を変えたら上げる（bincodeは項目名を持たないため）
+const CHECKPOINT_MAGIC: [u8; 4] = *b"EXCP";
+pub const CHECKPOINT_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint{
+    pub version: String, //examsimのバージョン
+    pub random_seed: u64, //ランダムシード
+    pub senario: i32, //シナリオ番号
+    pub epoch: i32, //次に実行するエポック
+    pub colleges: Vec<College>, //次エポック用の大学エージェント（履歴付き）
+    pub ronin: Vec<Ronin>, //次エポックに再受験する浪人生
+}
+
+impl Checkpoint {
+    pub fn new(conf: &Config, epoch: i32, colleges: &[College], ronin: &[Ronin]) -> Self{
+        Self{
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            random_seed: conf.random_seed,
+            senario: conf.senario,
+            epoch,
+            colleges: colleges.to_vec(),
+            ronin: ronin.to_vec(),
+        }
+    }
+
+    // 出力先Dirに保存。書き込み途中で落ちても前回分が残るように一時ファイルから置き換える
+    pub fn save(&self, dir: &str) -> Result<()>{
+        let path = Path::new(dir).join(CHECKPOINT_FILE);
+        let tmp = path.with_extension("tmp");
+        let mut wtr = BufWriter::new(File::create(&tmp)?);
+        wtr.write_all(&CHECKPOINT_MAGIC)?;
+        bincode::serialize_into(&mut wtr, &CHECKPOINT_FORMAT)?;
+        bincode::serialize_into(&mut wtr, self)?;
+        wtr.flush()?;
+        drop(wtr);
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self>{
+        let mut rdr = BufReader::new(File::open(path)
+            .with_context(|| format!("チェックポイント {:?} が開けません", path))?);
+        // 形式が違うと中身を読み違えるので、本体を読む前に確認する
+        let mut magic = [0u8; 4];
+        rdr.read_exact(&mut magic)
+            .with_context(|| format!("チェックポイント {:?} が読み込めません", path))?;
+        if magic != CHECKPOINT_MAGIC {
+            return Err(anyhow!("{:?} はチェックポイントではないか、形式の番号がない古い形式です", path))
+        }
+        let format: u32 = bincode::deserialize_from(&mut rdr)
+            .with_context(|| format!("チェックポイント {:?} が読み込めません", path))?;
+        if format != CHECKPOINT_FORMAT {
+            return Err(anyhow!("チェックポイント {:?} の形式 {} がこのexamsimの形式 {} と一致しません。同じ形式のexamsimで再開してください",
+                path, format, CHECKPOINT_FORMAT))
+        }
+        let cp: Self = bincode::deserialize_from(rdr)
+            .with_context(|| format!("チェックポイント {:?} が読み込めません", path))?;
+        Ok(cp)
+    }
+
+    // 設定との整合性を確認。シードやシナリオが違うと中断しない実行と同じ結果にならない
+    pub fn check(&self, conf: &Config) -> Result<()>{
+        if self.version != env!("CARGO_PKG_VERSION") {
+            return Err(anyhow!("チェックポイントのバージョン {} が examsim {} と一致しません",
+                self.version, env!("CARGO_PKG_VERSION")))
+        }
+        if self.random_seed != conf.random_seed {
+            return Err(anyhow!("チェックポイントの random_seed = {} が設定 {} と一致しません",
+                self.random_seed, conf.random_seed))
+        }
+        if self.senario != conf.senario {
+            return Err(anyhow!("チェックポイントの senario = {} が設定 {} と一致しません",
+                self.senario, conf.senario))
+        }
+        Ok(())
+    }
+}
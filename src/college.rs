@@ -6,6 +6,8 @@ use std::cmp::min;
 
 use crate::student::{Student, Sid};
 use crate::config::Config;
+use crate::policy::{self, EnrollContext, PolicyKind};
+use crate::estimator::YieldEstimate;
 use crate::{Matrix};
 
 pub type Cid = usize; //大学ID
@@ -46,13 +48,20 @@ pub struct College{
     pub index: usize, //ソート後の連番。これが配列のインデックスになる。
     #[serde(default)]
     pub saved: bool, //定員割れで公立として救済されたかのフラグ
-    #[serde(default)]
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub s_vec: Vec<Sid>, //一次合格した受験生のインデックス
     #[serde(default)]
     pub new_enroll_num: usize, //今回の一次合格者総数最大値。私立用。
     #[serde(default)]
     pub add_enroll_num: usize, //今回の追加合格用人数。私立用。
+    #[serde(default)]
+    pub early_num: usize, //2026.10.18 今回の推薦・総合型選抜の入学者数。私立用。
+    #[serde(default)]
+    pub policy: Option<PolicyKind>, //2026.10.18 合格者数の決め方。空欄ならenroll_policy
+    #[serde(default)]
+    pub entrance_fee: u64, //2026.10.18 入学金（円）
+    #[serde(default)]
+    pub tuition: u64, //2026.10.18 初年度授業料（円）
 
     #[serde(default)]
     pub dev_history: Vec<f64>, //各ステップの偏差値履歴。
@@ -60,6 +69,16 @@ pub struct College{
     pub adm_history: Vec<i32>, //各ステップの入学者数履歴。
     #[serde(default)]
     pub fillrate_history: Vec<f64>, //各ステップの定員従属率履歴。
+    #[serde(default)]
+    pub yield_history: Vec<f64>, //2026.10.18 各ステップの歩留率（入学者数÷合格者数）履歴。欠損は0
+    #[serde(default)]
+    pub yield_est: YieldEstimate, //2026.10.18 一次合格の歩留率の推定
+    #[serde(default)]
+    pub add_yield_est: YieldEstimate, //2026.10.18 追加合格の歩留率の推定
+    #[serde(default)]
+    pub yield_est_history: Vec<f64>, //2026.10.18 各ステップ終了時の一次合格歩留率推定値の履歴。
+    #[serde(default)]
+    pub add_yield_est_history: Vec<f64>, //2026.10.18 各ステップ終了時の追加合格歩留率推定値の履歴。
 
 
 
@@ -76,9 +95,23 @@ impl College {
             college.score = (college.dev * 1000.0).round() as i32;
             college.seed = conf.random_seed;
             college.senario = conf.senario;
+            // 2026.10.18 入学金・授業料。fee_csvに指定があればその金額、なければ設置区分別の既定値
+            let (entrance_fee, tuition) = conf.fees.get(&college.cid).copied().unwrap_or_else(|| {
+                let i = (college.institute.clamp(1, 3) - 1) as usize;
+                (conf.entrance_fees[i], conf.tuitions[i])
+            });
+            college.entrance_fee = entrance_fee;
+            college.tuition = tuition;
             college.dev_history.push(college.dev);//シミュレーション前の偏差値
             college.fillrate_history.push(0.0);//シミュレーション前の充足率は0にしておく
             college.adm_history.push(0);//シミュレーション前の入学者数は0にしておく
+            college.yield_history.push(college.yield_rate());//シミュレーション前の歩留率は前年度実績
+            // 2026.10.18 歩留率の推定。一次合格は前年度実績で事前の値を更新しておく
+            college.yield_est = YieldEstimate::new(conf, conf.mean_yield_rate);
+            college.yield_est.update(conf, college.adm_num, college.passed_num);
+            college.add_yield_est = YieldEstimate::new(conf, conf.mean_add_yield_rate);
+            college.yield_est_history.push(college.yield_est.mean);
+            college.add_yield_est_history.push(college.add_yield_est.mean);
             // 2021.11.29 志願者数が0（欠損値）の場合は入学定員を代用する
             if college.applicant_num == 0 {
                 college.applicant_num = college.enroll;
@@ -88,16 +121,16 @@ impl College {
         }
         // 偏差値の昇順にソート
         colleges.par_sort_by(|a, b| a.score.cmp(&b.score));
-        for i in 0..colleges.len() {colleges[i].index = i}
+        for (i, c) in colleges.iter_mut().enumerate() {c.index = i}
         Ok(colleges)    
     }
 
     //1ステップ分の入試結果を反映した新しいエージェントを返す
-    pub fn update(&self, result: &CollegeResult) -> College{
+    pub fn update(&self, conf: &Config, result: &CollegeResult) -> College{
         let mut college = self.clone();
         college.epoch += 1; //1年分進める
         //フラグtrue時のみ大学偏差値を入学者偏差値で更新する
-        if Config::get().update_dev {
+        if conf.update_dev {
             college.dev = result.new_deviation;
         }
         college.score = (college.dev * 1000.0).round() as i32;
@@ -116,7 +149,14 @@ impl College {
         //定員充足率　入学者÷定員
         college.fillrate_history.push( result.admissons as f64 / college.enroll as f64 );
         //2021.12.11 入学者履歴
-        college.adm_history.push(result.admissons as i32);
+        college.adm_history.push(result.admissons);
+        // 2026.10.18 歩留率履歴
+        college.yield_history.push(college.yield_rate());
+        // 2026.10.18 歩留率の推定値を更新
+        college.yield_est.update(conf, college.adm_num, college.passed_num);
+        college.add_yield_est.update(conf, result.admisson_add as u32, result.enroll_add_count as u32);
+        college.yield_est_history.push(college.yield_est.mean);
+        college.add_yield_est_history.push(college.add_yield_est.mean);
 
         // 2021.11.29 志願者数更新
         college.applicant_num = result.apply_count as u32;
@@ -124,11 +164,15 @@ impl College {
         //次年度の合格者超過率
         //入試結果を元に次年度のあるべき（辞退者が出ても入学定員になる）定員超過率を計算
         //合格者数/入学者数
-        college.over_rate = result.enroll_1st_count  as f64 /  result.admissons as f64; 
+        // 2026.10.18 推薦・総合型選抜の入学者は除く。入学者が全員推薦・総合型選抜なら前年度の値のまま
+        let admissons_general = result.admissons - result.admisson_early;
+        if !(result.admisson_early > 0 && admissons_general == 0) {
+            college.over_rate = result.enroll_1st_count  as f64 /  admissons_general as f64;
+        }
 
         // 2021.11.23 接地の場合、2年目以降の新しい入学定員、収容定員を設定する。最終年度は不要。
-        if Config::get().grounding && college.epoch < Config::get().epochs as usize{
-            if let Some((new_enroll, new_capa)) = Config::get().enroll_capa_dics[self.epoch].get(&self.cid){
+        if conf.grounding && college.epoch < conf.epochs as usize{
+            if let Some((new_enroll, new_capa)) = conf.enroll_capa_dics[self.epoch].get(&self.cid){
                 college.enroll = *new_enroll as u32;
                 college.capa = *new_capa as u32;
             }
@@ -140,21 +184,32 @@ impl College {
     //私立一次合格者決定 
     pub fn enroll1(&mut self, conf: &Config, students: &[Student], candidates: &[usize]) -> Vec<Sid>{
         // 1。受験者の配列を取得。
-        let mut id_and_scores: Vec<(&usize, &i32)> = candidates.into_iter()
+        let mut id_and_scores: Vec<(&usize, &i32)> = candidates.iter()
             .map(|x| (x, students[*x].c_map.get(&self.index).unwrap()))
             .collect();
 
             // 2.前年度実績と今年度入学定員制限から合格者数を決定。
-        self.new_enroll_num = self.enroll_num(id_and_scores.len());
+        self.new_enroll_num = self.enroll_num(conf, id_and_scores.len());
+        // 2026.10.18 推薦・総合型選抜で埋まった定員の割合だけ減らす
+        if self.early_num > 0 {
+            let general_rate = 1.0 - (self.early_num as f64 / self.enroll as f64).min(1.0);
+            self.new_enroll_num = (self.new_enroll_num as f64 * general_rate).round() as usize;
+        }
         // 追加合格用人数を設定
-        self.add_enroll_num = (self.new_enroll_num as f64 * conf.enroll_add_rate).round() as usize;
-        self.new_enroll_num -=  self.add_enroll_num; //追加合格分を引く
+        let add_share = (self.new_enroll_num as f64 * conf.enroll_add_rate).round() as usize;
+        self.new_enroll_num -=  add_share; //追加合格分を引く
+        // 2026.10.18 adaptiveは追加合格分の入学者を見込む人数を、一次合格と追加合格の歩留率の推定値の比で補正する
+        self.add_enroll_num = if self.policy_kind(conf) == PolicyKind::Adaptive {
+            (add_share as f64 * self.yield_est.rate() / self.add_yield_est.rate()).round() as usize
+        } else {
+            add_share
+        };
         // eprintln!("add_enroll_num:{:?}", self.add_enroll_num);
 
         //3.成績の良い順に合格者を決定
         id_and_scores.sort_by(|a, b| b.1.cmp(a.1));
         // println!("Coll idx: {:?} enroll_num:{:?} id&score.len :{:?}", self.index, enroll_num, id_and_scores.len());
-        let s_vec = (0..min(id_and_scores.len(), self.new_enroll_num)).into_iter()
+        let s_vec = (0..min(id_and_scores.len(), self.new_enroll_num))
             .map(|x|*(id_and_scores[x].0)).collect::<Vec<Sid>>();
         //4. 合格者を記録
         self.s_vec = s_vec.clone();
@@ -162,17 +217,18 @@ impl College {
     }
 
     //国公立合格者決定 
-    pub fn enroll2(&mut self, students: &[Student], candidates: &[usize]) -> Vec<Sid>{
+    pub fn enroll2(&mut self, quota: usize, students: &[Student], candidates: &[usize]) -> Vec<Sid>{
         // 1.前年度実績と今年度入学定員制限から合格者数を決定。-> 12.31 入学者数と同一にする
-        self.new_enroll_num = self.enroll as usize;
+        // 2026.10.18 日程別の募集人員
+        self.new_enroll_num = quota;
         // ２。受験者の配列を取得。
-        let mut id_and_scores: Vec<(&usize, &i32)> = candidates.into_iter()
+        let mut id_and_scores: Vec<(&usize, &i32)> = candidates.iter()
             .map(|x| (x, students[*x].c_map.get(&self.index).unwrap()))
             .collect();
         //3.成績の良い順に合格者を決定
         id_and_scores.sort_by(|a, b| b.1.cmp(a.1));
         // println!("Coll idx: {:?} enroll_num:{:?} id&score.len :{:?}", self.index, enroll_num, id_and_scores.len());
-        let s_vec = (0..min(id_and_scores.len(), self.new_enroll_num)).into_iter()
+        let s_vec = (0..min(id_and_scores.len(), self.new_enroll_num))
             .map(|x|*(id_and_scores[x].0)).collect::<Vec<Sid>>();
         //4. 合格者を記録
         self.s_vec = s_vec.clone();
@@ -182,23 +238,20 @@ impl College {
     //私立追加合格者決定 
     pub fn enroll3(&mut self, conf: &Config, students: &[Student], matrix: &Matrix, idx: usize) -> Vec<Sid>{
         // 1.現在の入学者数を計算
-        let statuss: Vec<(usize,u8)> = matrix.outer_view(idx).unwrap().indices().into_iter()
+        let statuss: Vec<(usize,u8)> = matrix.outer_view(idx).unwrap().indices().iter()
             .map(|col| (*col, *matrix.get(idx, *col).unwrap()))
             .collect();
         let current_admisson_num = statuss.iter()
             .filter(|(_, val)|  *val == Config::R_ADMISSION_1ST ||
-                                *val == Config::R_ADMISSION_RSV )
+                                *val == Config::R_ADMISSION_RSV ||
+                                *val == Config::R_ADMISSION_EARLY )
             .count();
         
         // 2021.11.29 入学定員でなく、入学定員×定員超過率の数値をベースにする。
         // 2021.12.31 入学定員に戻す．
         let base_line = self.enroll as usize;
         
-        let diff = if current_admisson_num > base_line{
-                0 as usize
-            }else{
-                base_line  - current_admisson_num
-            };
+        let diff = base_line.saturating_sub(current_admisson_num);
         if  diff > 0 { //不足
             //差分に追加合格用人数を上乗せ
             //2021.12.31 差分に今年度超過率を乗じた値にする 
@@ -217,68 +270,88 @@ impl College {
             //3.成績の良い順に合格者を決定
             id_and_scores.sort_by(|a, b| b.1.cmp(a.1));
             // println!("Coll idx: {:?} enroll_num:{:?} id&score.len :{:?}", self.index, enroll_num, id_and_scores.len());
-            let s_vec = (0..min(id_and_scores.len(), limit)).into_iter()
+            (0..min(id_and_scores.len(), limit))
                 // 2021.10.5 偏差値による足切り
                 .filter(|x| *(id_and_scores[*x].1) >= limit_score)
-                .map(|x|*(id_and_scores[x].0)).collect::<Vec<Sid>>();
-            s_vec
+                .map(|x|*(id_and_scores[x].0)).collect::<Vec<Sid>>()
         } else {
             Vec::new()
         }
     }
 
-    // 今年度の合格者数を計算
-    fn enroll_num(&mut self, applicate_num: usize) -> usize{
-        //2021.11.21 私立のみ変化。国公立は1.0固定
-        if self.institute != Config::PRIVATE {
-            return self.enroll as usize;
+    // 2026.10.18 国公立の日程別募集人員。national_quota_csvに指定があればその人数、なければ入学定員 x 日程別の比率
+    pub fn round_quota(&self, conf: &Config, round: usize) -> usize{
+        if let Some(quotas) = conf.national_quotas.get(&self.cid) {
+            return quotas.get(round).copied().unwrap_or(0) as usize
         }
+        (self.enroll as f64 * conf.national_round_rates[round]).round() as usize
+    }
 
+    // 今年度の合格者数を計算
+    // 2026.10.18 計算方法はEnrollmentPolicyで切り替える
+    fn enroll_num(&mut self, conf: &Config, applicate_num: usize) -> usize{
+        //2021.11.21 私立のみ変化。国公立は1.0固定 -> 2026.10.18 国公立はnational_quotaでfixed以外のときだけ呼ぶ
+        let kind = self.policy_kind(conf);
         self.own_scale = self.college_scale();
-        let before_current: (usize, usize);
-        let this_year = Config::get().start_year + self.epoch;
+        let this_year = conf.start_year + self.epoch;
         let mut limit_table = Config::MAX_ENROLLMENT_RATES.to_vec();
-        limit_table.push(Config::get().new_limits);
-        if !Config::get().small_college_support { // 2021.11.19 小規模優遇なし
-            before_current = match this_year{
+        limit_table.push(conf.new_limits);
+        let before_current: (usize, usize) = if !conf.small_college_support { // 2021.11.19 小規模優遇なし
+            match this_year{
                 0..=2015    => (0,0),//変化なし
                 2016..=2018 => (this_year - 2016, this_year - 2015),
                 _ => (3,3), //変化なし
-            };
+            }
         } else {
-            before_current = match this_year{
+            match this_year{
                 0..=2015    => (0,0),//変化なし
                 2016..=2022 => (this_year - 2016, this_year - 2015),
                 _ => (7,7), //変化なし
-            };
+            }
         };
         self.current_rate = limit_table[before_current.1][self.own_scale];
 
-        //2021.12.12 アルゴリズム改善．旧バージョンも残す
-        if Config::get().enroll_algo_version == 2 {
-            // 歩留率計算．入学者数または合格者数が欠損（0）の場合は2014年度の私立大学平均を使う
-            let yield_rate =  if self.passed_num == 0 || self.adm_num == 0{
-                    Config::get().mean_yield_rate
-                } else {
-                    self.adm_num as f64 / self.passed_num as f64
-                };
-            let enroll =  self.enroll as f64 * self.current_rate / yield_rate;
-            // 2021.12.31 2年目以降は前年度受験者数と今回受験者数の変化率で補正する
-            let apply_change_rate = if self.epoch == 0 { 1 as f64 }else{
-                self.applicate_num as f64 / applicate_num  as f64
-            };
-            // (enroll as f64 *  apply_change_rate) as usize
-            // 2022.01.01 超過率に対する感度
-            (enroll as f64 *  apply_change_rate * Config::get().sensitivity) as usize
+        let ctx = EnrollContext{
+            applicate_num,
+            current_rate: self.current_rate,
+            before_rate: limit_table[before_current.0][self.own_scale],
+        };
+        policy::policy(kind).enroll_num(conf, self, &ctx)
+    }
+
+    // 2026.10.18 合格者数の決め方。大学CSVのpolicy列、設置区分別の指定（国立national_policy・公立public_policy・私立enroll_policy）の順に優先
+    // 未指定なら国公立は入学定員そのまま、私立はenroll_algo_version
+    pub fn policy_kind(&self, conf: &Config) -> PolicyKind{
+        if let Some(kind) = self.policy {
+            return kind
+        }
+        match self.institute {
+            Config::PRIVATE => conf.enroll_policy.unwrap_or_else(|| PolicyKind::from_version(conf.enroll_algo_version)),
+            Config::NATIONAL => conf.national_policy.unwrap_or(PolicyKind::Fixed),
+            _ => conf.public_policy.unwrap_or(PolicyKind::Fixed),
+        }
+    }
+
+    // 2026.10.18 国公立の日程別の合格者数。fixed以外の決め方では、合格者数と入学定員の比で日程別の募集人員を増減する
+    pub fn national_quota(&mut self, conf: &Config, round: usize, applicate_num: usize) -> usize{
+        let quota = self.round_quota(conf, round);
+        if self.enroll == 0 || self.policy_kind(conf) == PolicyKind::Fixed {
+            return quota
+        }
+        let num = self.enroll_num(conf, applicate_num);
+        (quota as f64 * num as f64 / self.enroll as f64).round() as usize
+    }
 
+    // 2026.10.18 前年度の歩留率。入学者数または合格者数が欠損（0）なら0
+    pub fn yield_rate(&self) -> f64{
+        if self.passed_num == 0 || self.adm_num == 0 {
+            0.0
         } else {
-            // 2016(0)以前と2016(1)の増減率を取得。
-            let limit_change_rate = self.current_rate / limit_table[before_current.0][self.own_scale];        
-            (self.enroll as f64 * self.over_rate * limit_change_rate).round() as usize
+            self.adm_num as f64 / self.passed_num as f64
         }
     }
 
-    fn college_scale(&self) -> usize {
+    pub fn college_scale(&self) -> usize {
         if self.capa >= 8000 { 0 }
         else if self.capa >= 4000{ 1 }
         else { 2 }
@@ -287,7 +360,7 @@ impl College {
 }
 
 // シミュレーション結果CSV
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct CollegeResult{
     //エポック数
     pub epoch: i32,
@@ -296,8 +369,13 @@ pub struct CollegeResult{
     pub cid: Cid, //旺文社の大学番号
     pub name: String,  //  大学名
     pub institute: u8, // 設置区分：1国立 2公立 3私立
+    pub pref: u8, // 2026.10.18 都道府県番号：1-47
+    pub urban: String, // 2026.10.18 都市区分表記："地方"or"都市圏"
+    pub own_scale: usize, // 2026.10.18 大学規模 0:大 1:中 2:小
+    #[serde(deserialize_with = "crate::sink::f64_or_nan")]
     pub dev: f64, // 偏差値
     pub enroll: u32, //　入学定員数
+    #[serde(deserialize_with = "crate::sink::f64_or_nan")]
     pub over_rate: f64, //合格者超過率
 
     //シミュレーション結果
@@ -309,13 +387,40 @@ pub struct CollegeResult{
     pub enroll_add_count: i32, //追加合格数
     pub paid_only_count: i32, //入学金納付のみ
 
+    pub admisson_early: i32, //2026.10.18 推薦・総合型選抜で入学
     pub admisson_1st: i32, //一次、国立合格で入学
     pub admisson_rsv: i32, //一次保留後入学
     pub admisson_add: i32, //追加合格入学
 
     pub admissons: i32, //最終入学者数
+    #[serde(deserialize_with = "crate::sink::f64_or_nan")]
     pub new_deviation: f64, //入学者偏差値平均
-    pub payments: i32, //入学金徴収総額
+    pub payments: i32, //入学金納付者数（入学者＋入学金納付後辞退者）
+
+    // 2026.10.18 入学金・授業料（円）
+    pub entrance_fee: u64, //入学金
+    pub tuition: u64, //初年度授業料
+    pub fee_revenue: u64, //入学金収入 入学金 x 入学金納付者数
+    pub forfeited_fees: u64, //うち入学金納付後辞退者の入学金
+    pub tuition_revenue: u64, //授業料収入 初年度授業料 x 最終入学者数
+}
+
+// 2026.10.18 国公立の日程別募集人員CSV 1行 = 大学 x 日程
+#[derive(Debug,Clone,Default,Deserialize)]
+pub struct RoundQuota{
+    pub cid: Cid, // 旺文社大学番号
+    pub round: usize, // 日程 1:前期 2:中期/後期 ... national_round_ratesの順
+    pub quota: u32, // 募集人員
+}
+
+// 2026.10.18 大学別の入学金・授業料CSV
+#[derive(Debug,Clone,Default,Deserialize)]
+pub struct CollegeFee{
+    pub cid: Cid, // 旺文社大学番号
+    #[serde(alias = "入学金")]
+    pub entrance_fee: u64, // 入学金（円）
+    #[serde(alias = "授業料")]
+    pub tuition: u64, // 初年度授業料（円）
 }
 
 // 2021.11.23 入学定員・収容人数CSV
@@ -1,38 +1,81 @@
 use std::collections::HashMap;
 use std::process::exit;
-use anyhow::{Context, Result};
-use clap::{App, Arg, crate_version};
+use anyhow::{anyhow, Context, Result};
+use clap::{App, Arg, ArgMatches, crate_version};
 use std::fs;
-use std::io::Read;
-use serde::Deserialize;
-use once_cell::sync::OnceCell;
+use std::path::{Path, PathBuf};
+use toml::value::Table;
+use serde::{Deserialize, Serialize};
 use chrono::Local;
 use csv::ReaderBuilder;
 
 
-use crate::college::EnrollAndCapa;
-
-// グローバルな設定情報オブジェクト
-pub static CONFIG: OnceCell<Config> = OnceCell::new();
+use crate::college::{Cid, CollegeFee, EnrollAndCapa, RoundQuota};
+use crate::sweep;
+use crate::geo;
+use crate::population::{self, StudentDist, StudentSource};
+use crate::sink::OutputFormat;
+use crate::output::HistoryFormat;
+use crate::policy::PolicyKind;
+use crate::estimator::YieldEstimator;
+
+// 継承元の設定ファイルを指定するキー
+const EXTENDS_KEY: &str = "extends";
+
+// 2026.10.18 受験生生成の乱数方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RngMode {
+    Legacy, //random_seedから毎エポック同じ偏差値列と受験生別シード（random_seed + i）を使う
+    #[default]
+    Epoch, //エポック別に独立した乱数系列を使う
+}
 
-#[derive(Debug,Clone,Deserialize)]
+// 2026.10.18 設定ファイルにない項目はDefault（シナリオ1の値）を使う
+#[derive(Debug,Clone,Deserialize,Serialize)]
+#[serde(default)]
 pub struct Config{
     pub initial_college_csv: String,
     pub student_number: Vec<usize>,
     pub student_dev_mu: f64,
     pub student_dev_sigma: f64,
+    // 2026.10.18 エポック別の平均と標準偏差。空なら上の値を全エポックで使う
+    pub student_dev_mus: Vec<f64>,
+    pub student_dev_sigmas: Vec<f64>,
+    // 2026.10.18 偏差値の分布 normal / skewnormal / mixture
+    pub student_dist: StudentDist,
+    pub student_dev_skew: f64, //歪正規分布の形状パラメータ。正で右に裾が長い
+    pub student_dev_mixture: Vec<[f64; 3]>, //混合正規分布の成分 [重み, 平均, 標準偏差]
+    // 2026.10.18 偏差値を範囲内に切断する
+    pub student_dev_truncate: bool,
+    pub student_dev_bounds: [f64; 2],
+    // 2026.10.18 受験生母集団 normal / csv / histogram。csv, histogramは年度別のファイルを読む
+    pub student_source: StudentSource,
+    pub student_csv_dir: String,
+    pub student_csv_name: String,
     pub random_seed: u64,
     pub output_dir_base: String,
-    pub output_dir: String,
+    #[serde(skip)]
+    pub output_dir: String, //実行時に決定
 
     pub national_prob: f64,
     pub national_range: [i32; 2],
+    // 2026.10.18 国公立の日程（前期・中期・後期）別募集人員の入学定員に対する比率。要素数が日程数
+    pub national_round_rates: Vec<f64>,
+    // 2026.10.18 大学別の日程別募集人員CSV（cid,round,quota）。空ならnational_round_ratesで按分
+    pub national_quota_csv: String,
+    #[serde(skip)]
+    pub national_quotas: HashMap<Cid, Vec<u32>>, //実行時にnational_quota_csvから作成
     pub college_rank_lower: [i32; 3],
     pub college_rank_upper: [i32; 3],
     pub college_rank_select_number: [[usize; 3]; 2],
     pub college_rank_select_number_diamond: [[usize; 3]; 2], //2021.12.31
     
     pub first_pattern_rate: f64,
+    // 2026.10.18 推薦・総合型選抜。私立大学が一般選抜の前に入学定員のこの比率まで入学者を決める。0なら実施しない
+    pub early_rate: f64,
+    // 2026.10.18 推薦・総合型選抜の対象となる受験生の偏差値範囲。大学偏差値からの差 [下限, 上限]
+    pub early_band: [i32; 2],
     pub enroll_add_rate: f64,
     pub enroll_add_lower: i32,
 
@@ -47,17 +90,38 @@ pub struct Config{
 
     pub enroll_capa_csv_dir: String,
     pub enroll_capa_csv_name: String,
-    pub enroll_capa_dics: Vec<HashMap<usize, (i32,i32)>>,
+    #[serde(skip)]
+    pub enroll_capa_dics: Vec<HashMap<usize, (i32,i32)>>, //実行時に定員情報CSVから作成
 
     pub grounding: bool,
 
     pub new_limits: [f64; 3],
 
     pub logging: bool,
+    // 2026.10.18 ログの出力形式 csv / jsonl / bin
+    pub college_output_format: OutputFormat,
+    pub student_output_format: OutputFormat,
+    // 2026.10.18 履歴の出力形式 stdout / json / csv
+    pub history_format: HistoryFormat,
 
     pub senario: i32, 
     
     pub enroll_algo_version: i32,
+    // 2026.10.18 私立の合格者数の決め方 fixed / v1 / v2 / average / quantile / pid / adaptive。未指定ならenroll_algo_version
+    // 大学CSVのpolicy列で大学別に指定したものが優先
+    pub enroll_policy: Option<PolicyKind>,
+    // 2026.10.18 国立・公立の合格者数の決め方。未指定なら入学定員そのまま（fixed）。日程別の募集人員を合格者数と入学定員の比で増減する
+    pub national_policy: Option<PolicyKind>,
+    pub public_policy: Option<PolicyKind>,
+    pub policy_window: usize, //average：歩留率の平均をとる年数
+    pub policy_quantile: f64, //quantile：歩留率の分位点
+    pub policy_target_fill: f64, //pid：目標とする定員充足率
+    pub policy_pid: [f64; 3], //pid：比例・積分・微分ゲイン
+    // 2026.10.18 歩留率の学習 ewma / beta。enroll_policy = "adaptive" で合格者数に使う
+    pub yield_estimator: YieldEstimator,
+    pub yield_smoothing: f64, //ewma：今年度の実績の重み
+    pub yield_prior_strength: f64, //beta：事前分布の強さ（合格者数換算）
+    pub mean_add_yield_rate: f64, //追加合格の歩留率の事前の値
 
     pub mean_yield_rate: f64,
 
@@ -69,6 +133,159 @@ pub struct Config{
     pub sensitivity: f64,
     // 2022.01.02
     pub wait_addtion_rate: f64,
+
+    // 2026.10.18 浪人。入学先のない受験生がこの確率で次エポックに再受験する。0なら浪人しない
+    pub ronin_rate: f64,
+    pub ronin_gain: f64, //浪人1年での偏差値の上昇
+    pub ronin_max_years: u32, //浪人年数の上限
+
+    // 2026.10.18 入学金・授業料（円）。設置区分別（国立, 公立, 私立）の既定値
+    pub entrance_fees: [u64; 3],
+    pub tuitions: [u64; 3],
+    // 大学別の入学金・授業料CSV（cid,entrance_fee,tuition）。空なら設置区分別の既定値
+    pub fee_csv: String,
+    #[serde(skip)]
+    pub fees: HashMap<Cid, (u64, u64)>, //実行時にfee_csvから作成
+
+    // 2026.10.18 資金制約。有効時は受験生の予算の範囲で入学金を納付する私立を選ぶ
+    pub budget_model: bool,
+    pub student_budget_mu: f64, //入学金に充てられる予算（円）の平均
+    pub student_budget_sigma: f64, //予算の標準偏差。0未満は0にする
+    pub reserve_max: usize, //入学金を納付（保留）する私立の最大校数
+
+    // 2026.10.18 地理モデル。受験生の出身都道府県と大学の所在地・都市区分を大学選択に反映する
+    pub geo_model: bool,
+    pub student_pref_weights: Vec<f64>, //出身都道府県の重み（都道府県番号順47個）。空なら人口比
+    pub geo_distance_scale: f64, //距離減衰のスケール（km）。0なら距離減衰なし
+    pub geo_home_bonus: f64, //地元（同一都道府県）の大学の倍率
+    pub geo_urban_attraction: f64, //都市圏の大学の倍率
+
+    // 2026.10.18 受験生生成の乱数方式 epoch（エポック別の独立した系列）/ legacy（毎エポック同じ系列）
+    pub rng_mode: RngMode,
+
+    // 2026.10.18 モンテカルロ反復回数．シードをrandom_seedから1ずつ変えて実行する．1以下は通常実行
+    pub replicates: usize,
+
+    // 2026.10.18 エポック終了毎に出力先Dirへチェックポイントを保存する
+    pub checkpoint: bool,
+    // 2026.10.18 --resumeで指定したチェックポイントファイル．実行時に決定
+    #[serde(skip)]
+    pub resume: String,
+
+    // 2026.10.18 スイープ実行時のラベル．実行時に決定
+    #[serde(skip)]
+    pub label: String,
+    // 2026.10.18 実行記録用．設定ファイル名とコマンドライン引数による上書き
+    #[serde(skip)]
+    pub config_file: String,
+    #[serde(skip)]
+    pub cli_overrides: Vec<String>,
+}
+
+// 2026.10.18 設定項目の既定値。configS1.toml（シナリオ1 現状維持）と同じ値
+impl Default for Config {
+    fn default() -> Self{
+        Self{
+            initial_college_csv: "u2020v03.csv".to_string(),
+            //2020年から2031年の志願者数推計値
+            student_number: vec![659135, 644337, 633121, 619473, 600142, 615444,
+                                 616657, 613526, 604431, 603202, 592806, 584150],
+            //受験生偏差値の平均と標準偏差 2018データから
+            student_dev_mu: 54.9578,
+            student_dev_sigma: 8.8676,
+            student_dev_mus: Vec::new(),
+            student_dev_sigmas: Vec::new(),
+            student_dist: StudentDist::Normal,
+            student_dev_skew: 0.0,
+            student_dev_mixture: Vec::new(),
+            student_dev_truncate: false,
+            student_dev_bounds: [20.0, 90.0],
+            student_source: StudentSource::Normal,
+            student_csv_dir: "students/".to_string(),
+            student_csv_name: "_students.csv".to_string(),
+            random_seed: 42,
+            output_dir_base: "./output".to_string(),
+            output_dir: ".".to_string(),
+
+            national_prob: 0.8120,
+            national_range: [0, 5],
+            national_round_rates: vec![1.0],
+            national_quota_csv: String::new(),
+            national_quotas: HashMap::new(),
+            college_rank_lower: [3, -3, -5],
+            college_rank_upper: [5, 3, -3],
+            college_rank_select_number: [[4, 2, 1], [5, 2, 1]], //7校 or 8校
+            college_rank_select_number_diamond: [[2, 3, 2], [2, 4, 2]],
+
+            first_pattern_rate: 0.4458, //平均出願数7.5542（2020年度実績）
+            early_rate: 0.0,
+            early_band: [-5, 3],
+            enroll_add_rate: 0.0,
+            enroll_add_lower: 0, //偏差値足切りなし
+
+            epochs: 12,
+            start_year: 2020,
+
+            college_select_by_enroll: true,
+            small_college_support: false,
+            update_dev: false,
+
+            enroll_capa_csv_dir: "capa/".to_string(),
+            enroll_capa_csv_name: "_enroll_capa.csv".to_string(),
+            enroll_capa_dics: Vec::new(),
+
+            grounding: false,
+            new_limits: [1.0, 1.0, 1.0],
+            logging: false,
+            college_output_format: OutputFormat::Csv,
+            student_output_format: OutputFormat::Csv,
+            history_format: HistoryFormat::Stdout,
+            senario: 1,
+            enroll_algo_version: 1,
+            enroll_policy: None,
+            national_policy: None,
+            public_policy: None,
+            policy_window: 3,
+            policy_quantile: 0.75,
+            policy_target_fill: 1.0,
+            policy_pid: [0.5, 0.1, 0.0],
+            yield_estimator: YieldEstimator::Ewma,
+            yield_smoothing: 0.3,
+            yield_prior_strength: 10.0,
+            mean_add_yield_rate: 0.5293,
+            mean_yield_rate: 0.5293, //2020年度の私立大学平均歩留率
+
+            college_dev_rift: false,
+            college_dev_lower: 36348,
+            college_dev_upper: 78195,
+            sensitivity: 1.0,
+            wait_addtion_rate: 0.4,
+            ronin_rate: 0.0,
+            ronin_gain: 2.0,
+            ronin_max_years: 2,
+            entrance_fees: [282_000, 282_000, 245_951],
+            tuitions: [535_800, 535_800, 930_943],
+            fee_csv: String::new(),
+            fees: HashMap::new(),
+            budget_model: false,
+            student_budget_mu: 500_000.0,
+            student_budget_sigma: 250_000.0,
+            reserve_max: 2,
+
+            geo_model: false,
+            student_pref_weights: Vec::new(),
+            geo_distance_scale: 300.0,
+            geo_home_bonus: 2.0,
+            geo_urban_attraction: 1.0,
+            rng_mode: RngMode::Epoch,
+            replicates: 0,
+            checkpoint: false,
+            resume: String::new(),
+            label: String::new(),
+            config_file: String::new(),
+            cli_overrides: Vec::new(),
+        }
+    }
 }
 
 impl Config {
@@ -95,9 +312,10 @@ impl Config {
     pub const R_ADMISSION_2ND: u8 = 17; //国公立に合格し入学
     pub const R_ADMISSION_RSV: u8 = 39; //一次合格保留後私立入学
     pub const R_ADMISSION_3RD: u8 = 193; //追加入学決定
+    pub const R_ADMISSION_EARLY: u8 = 9; //2026.10.18 推薦・総合型選抜で入学（一般選抜に出願しない）
 
     //大学設定区分
-    // pub const NATIONAL: u8 = 1; //国立
+    pub const NATIONAL: u8 = 1; //国立
     // pub const PUBLIC: u8 = 2; //公立
     pub const PRIVATE: u8 = 3; //私立
 
@@ -116,8 +334,8 @@ impl Config {
     ///////////////////////////////////////////////////////
     // ここから関数定義
     // Configオブジェクト生成。　コマンドライン引数の設定ファイルから。
-    // Configオブジェクトは一度だけstaticで生成され、その後不変。
-    pub fn from_args() -> Result<()>{
+    // 2026.10.18 スイープファイルの場合は展開した実行数分のConfigを返す。
+    pub fn from_args() -> Result<Vec<Config>>{
         let matches = App::new("大学受験戦略シミュレーション")
             .version(crate_version!())
             .arg(Arg::with_name("CONFIG_FILE") 
@@ -135,26 +353,114 @@ impl Config {
                 .short("l")                         // ショートコマンド
                 .long("log")                       // ロングコマンド
             )
+            .arg(Arg::with_name("replicates")
+                .help("number of seeds to run and aggregate")
+                .short("r")
+                .long("replicates")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("set")
+                .help("override config item: --set key=value (value in TOML syntax)")
+                .long("set")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+            )
+            .arg(Arg::with_name("threads")
+                .help("number of worker threads (default: RAYON_NUM_THREADS or all cores)")
+                .long("threads")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("resume")
+                .help("resume from checkpoint file")
+                .long("resume")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("check_config")
+                .help("validate config file only")
+                .long("check-config")
+            )
             .get_matches();
 
+        // 2026.10.18 並列スレッド数。結果には影響しない
+        if let Some(threads) = matches.value_of("threads") {
+            let n = threads.parse::<usize>()
+                .with_context(|| format!("--threads {:?} は1以上の整数で指定してください", threads))?;
+            rayon::ThreadPoolBuilder::new().num_threads(n).build_global()?;
+            eprintln!("    スレッド数 = {}", rayon::current_num_threads());
+        }
+
         if let Some(filename) = matches.value_of("CONFIG_FILE") {
-            let mut f = fs::File::open(filename).expect("config toml file not found");
             eprintln!("    設定ファイル = {:?}", filename);
+            // 2026.10.18 extendsの継承元を統合
+            let value = toml::Value::Table(load_table(Path::new(filename))?);
+            // 2026.10.18 --set key=value の上書き。型はここで検査しておく
+            let sets = parse_sets(&matches)?;
+
+            // 2026.10.18 スイープファイルなら展開する
+            let is_sweep = sweep::is_sweep(&value);
+            let mut confs: Vec<Config> = Vec::new();
+            if is_sweep {
+                for run in sweep::expand(filename, value)? {
+                    let label = run.label;
+                    let mut table = run.table;
+                    apply_sets(&mut table, &sets);
+                    let mut cfg = Config::from_value(toml::Value::Table(table), &label)
+                        .with_context(|| format!("[{}] 設定項目が正しくありません", label))?;
+                    cfg.label = label;
+                    confs.push(cfg);
+                }
+            } else {
+                let mut value = value;
+                if let Some(table) = value.as_table_mut() {
+                    apply_sets(table, &sets);
+                }
+                let cfg = Config::from_value(value, "")
+                    .with_context(|| format!("設定ファイル {:?} の設定項目が正しくありません", filename))?;
+                confs.push(cfg);
+            }
+            for cfg in confs.iter_mut() {
+                cfg.config_file = filename.to_string();
+                cfg.apply_args(&matches)?;
+            }
 
-            let mut contents = String::new();
-            f.read_to_string(&mut contents).expect("config file read error");
-            let mut cfg: Config = toml::from_str(&contents).unwrap();
+            // 2026.10.18 設定値の検証。問題点をすべて列挙してエラーにする
+            let errors: Vec<String> = confs.iter()
+                .flat_map(|cfg| cfg.validate().into_iter()
+                    .map(move |e| if cfg.label.is_empty() { e } else { format!("[{}] {}", cfg.label, e) }))
+                .collect();
+            if !errors.is_empty() {
+                return Err(anyhow!("設定ファイル {:?} に{}件の問題があります\n    {}",
+                    filename, errors.len(), errors.join("\n    ")))
+            }
 
-            // 2021.12.08 ランダムシードの指定があれば設定ファイルの指定を上書き
-            if let Some(seed) = matches.value_of("seed"){
-                cfg.random_seed = seed.parse::<u64>().unwrap();
+            // 検証のみ。出力先Dirは作成しない
+            if matches.is_present("check_config") {
+                for cfg in confs.iter_mut() {
+                    cfg.prepare()?;
+                }
+                eprintln!("    設定ファイルの検証OK 実行数 = {}", confs.len());
+                exit(0)
             }
 
-            // 2021.12.23 ログ出力指定があれば設定ファイルの指定を上書き
-            if matches.is_present("logging"){
-                cfg.logging = true;
+            // 2026.10.18 スイープはラベル別の出力先Dirに振り分ける
+            if is_sweep {
+                let sweep_dir = sweep::make_sweep_dir(&confs[0].output_dir_base, filename)?;
+                eprintln!("    スイープ出力先Dir = {:?}", sweep_dir);
+                for cfg in confs.iter_mut() {
+                    cfg.output_dir = format!("{}/{}/", sweep_dir, cfg.label);
+                    fs::create_dir(&cfg.output_dir).context("dir cannot create")?;
+                    eprintln!("    [{}] ログ出力先Dir = {:?}", cfg.label, cfg.output_dir);
+                    cfg.write_effective()?;
+                    cfg.prepare()?;
+                }
+                let labels: Vec<String> = confs.iter().map(|c| c.label.clone()).collect();
+                sweep::write_manifest(&sweep_dir, &labels, &confs)?;
+                return Ok(confs)
             }
-            if cfg.logging{
+
+            let mut cfg = confs.remove(0);
+            if cfg.has_output_dir(){
                 cfg.output_dir = Config::get_output_dirname(& cfg)?;
                 eprintln!("    ログ出力先Dir = {:?}", cfg.output_dir);
             } else {
@@ -162,28 +468,300 @@ impl Config {
             }
 
             eprintln!("    random seed = {:?}", cfg.random_seed);
+            if cfg.replicates > 1{
+                eprintln!("    反復回数 = {:?}", cfg.replicates);
+            }
 
-            // 2021.11.23 接地用　2年目以降定員情報Vec作成
-            if cfg.grounding {
-                cfg.enroll_capa_dics = Config::make_enroll_capa_info(&cfg)?;
+            //統合後の実効設定を出力先Dirに保存
+            if cfg.has_output_dir(){
+                cfg.write_effective()?;
             }
 
-            //設定ファイルを出力先Dirにコピー
-            // if cfg.logging{
-            //     fs::copy(filename, format!("{}/{}",cfg.output_dir, filename)).unwrap();
-            // }
+            cfg.prepare()?;
 
-            CONFIG.set(cfg).unwrap();
-            Ok(())
+            Ok(vec![cfg])
         } else {
             eprintln!("設定ファイル名が指定されていません。");
             exit(1)
         }
     }
 
-    // 生成済みのConfigオブジェクトを返す
-    pub fn get() -> &'static Config{
-        CONFIG.get().expect("Not initalized Config")
+    // 2026.10.18 TOMLからConfigを生成。不明な項目と実行時に決まる項目は警告を出して無視する
+    fn from_value(value: toml::Value, label: &str) -> Result<Config>{
+        let mut ignored: Vec<String> = Vec::new();
+        let cfg: Config = serde_ignored::deserialize(value, |path| ignored.push(path.to_string()))?;
+        let prefix = if label.is_empty() { String::new() } else { format!("[{}] ", label) };
+        for key in ignored {
+            match key.as_str() {
+                "output_dir" | "enroll_capa_dics" | "national_quotas" | "fees" | "label"
+                | "resume" | "config_file" | "cli_overrides" =>
+                    eprintln!("    {}警告: {} は実行時に決定するため設定ファイルの値は無視します", prefix, key),
+                _ => eprintln!("    {}警告: 不明な設定項目 {} は無視します", prefix, key),
+            }
+        }
+        Ok(cfg)
+    }
+
+    // コマンドライン引数の指定で設定ファイルの指定を上書き
+    fn apply_args(&mut self, matches: &ArgMatches) -> Result<()>{
+        // 2021.12.08 ランダムシードの指定があれば設定ファイルの指定を上書き
+        if let Some(seed) = matches.value_of("seed"){
+            self.random_seed = seed.parse::<u64>()
+                .with_context(|| format!("--seed {:?} は0以上の整数で指定してください", seed))?;
+            self.cli_overrides.push(format!("--seed {}", seed));
+        }
+
+        // 2021.12.23 ログ出力指定があれば設定ファイルの指定を上書き
+        if matches.is_present("logging"){
+            self.logging = true;
+            self.cli_overrides.push("--log".to_string());
+        }
+        // 2026.10.18 反復回数の指定があれば設定ファイルの指定を上書き
+        if let Some(replicates) = matches.value_of("replicates"){
+            self.replicates = replicates.parse::<usize>()
+                .with_context(|| format!("--replicates {:?} は0以上の整数で指定してください", replicates))?;
+            self.cli_overrides.push(format!("--replicates {}", replicates));
+        }
+        if let Some(threads) = matches.value_of("threads"){
+            self.cli_overrides.push(format!("--threads {}", threads));
+        }
+        // 2026.10.18 チェックポイントから再開
+        if let Some(resume) = matches.value_of("resume"){
+            self.resume = resume.to_string();
+            self.cli_overrides.push(format!("--resume {}", resume));
+        }
+        // 2026.10.18 --setの値は設定ファイルの統合時に適用済み。記録のみ
+        if let Some(sets) = matches.values_of("set"){
+            for s in sets {
+                self.cli_overrides.push(format!("--set {}", s));
+            }
+        }
+        Ok(())
+    }
+
+    // 2026.10.18 エポックの受験生偏差値の平均と標準偏差
+    pub fn student_dev_params(&self, epoch: usize) -> (f64, f64){
+        let mu = self.student_dev_mus.get(epoch).copied().unwrap_or(self.student_dev_mu);
+        let sigma = self.student_dev_sigmas.get(epoch).copied().unwrap_or(self.student_dev_sigma);
+        (mu, sigma)
+    }
+
+    // 出力先Dirを作成する実行か。ログ出力、反復実行、スイープ実行、履歴のファイル出力の場合
+    pub fn has_output_dir(&self) -> bool{
+        self.logging || self.replicates > 1 || !self.label.is_empty()
+            || self.history_format != HistoryFormat::Stdout || self.checkpoint
+    }
+
+    // 2026.10.18 設定値の検証。問題点を項目名と値付きですべて返す。
+    pub fn validate(&self) -> Vec<String>{
+        let mut errors: Vec<String> = Vec::new();
+
+        if self.epochs < 1 {
+            errors.push(format!("epochs = {} : 1以上で指定してください", self.epochs));
+        } else if self.student_source != StudentSource::Csv && self.student_number.len() < self.epochs as usize {
+            errors.push(format!("student_number の要素数 {} が epochs = {} より少ない",
+                self.student_number.len(), self.epochs));
+        }
+        if !is_positive(self.student_dev_sigma) {
+            errors.push(format!("student_dev_sigma = {} : 正の値で指定してください", self.student_dev_sigma));
+        }
+        for (name, values) in [("student_dev_mus", &self.student_dev_mus), ("student_dev_sigmas", &self.student_dev_sigmas)].iter() {
+            if !values.is_empty() && values.len() < self.epochs.max(0) as usize {
+                errors.push(format!("{} の要素数 {} が epochs = {} より少ない", name, values.len(), self.epochs));
+            }
+        }
+        // 2026.10.18 エポック別の平均・標準偏差は設定値から生成する分布のみ。混合正規分布・受験生CSV・ヒストグラムでは使われない
+        if (!self.student_dev_mus.is_empty() || !self.student_dev_sigmas.is_empty())
+            && (self.student_dist == StudentDist::Mixture || self.student_source != StudentSource::Normal) {
+            errors.push("student_dev_mus, student_dev_sigmas は student_dist = mixture、student_source = csv / histogram では使えません".to_string());
+        }
+        if self.student_dev_sigmas.iter().any(|x| !is_positive(*x)) {
+            errors.push(format!("student_dev_sigmas = {:?} : 正の値で指定してください", self.student_dev_sigmas));
+        }
+        if !self.student_dev_skew.is_finite() {
+            errors.push(format!("student_dev_skew = {} : 有限の値で指定してください", self.student_dev_skew));
+        }
+        if self.student_dist == StudentDist::Mixture {
+            if self.student_dev_mixture.is_empty() {
+                errors.push("student_dist = mixture : student_dev_mixture に成分を指定してください".to_string());
+            }
+            for c in self.student_dev_mixture.iter() {
+                if !is_positive(c[0]) || !is_positive(c[2]) {
+                    errors.push(format!("student_dev_mixture の成分 {:?} : 重みと標準偏差は正の値で指定してください", c));
+                }
+            }
+        }
+        if self.student_dev_truncate && self.student_dev_bounds[0] >= self.student_dev_bounds[1] {
+            errors.push(format!("student_dev_bounds = {:?} : 下限が上限以上", self.student_dev_bounds));
+        }
+
+        // 確率
+        for (name, value) in [
+            ("national_prob", self.national_prob),
+            ("first_pattern_rate", self.first_pattern_rate),
+            ("wait_addtion_rate", self.wait_addtion_rate),
+            ("ronin_rate", self.ronin_rate),
+            ("enroll_add_rate", self.enroll_add_rate),
+        ].iter() {
+            if !(0.0..=1.0).contains(value) {
+                errors.push(format!("{} = {} : 0以上1以下で指定してください", name, value));
+            }
+        }
+        if self.budget_model && (!self.student_budget_mu.is_finite() || !is_non_negative(self.student_budget_sigma)) {
+            errors.push(format!("student_budget_mu = {}, student_budget_sigma = {} : 有限の値、標準偏差は0以上で指定してください",
+                self.student_budget_mu, self.student_budget_sigma));
+        }
+        if !self.ronin_gain.is_finite() {
+            errors.push(format!("ronin_gain = {} : 有限の値で指定してください", self.ronin_gain));
+        }
+        if !(0.0..1.0).contains(&self.early_rate) {
+            errors.push(format!("early_rate = {} : 0以上1未満で指定してください", self.early_rate));
+        }
+        if self.early_band[0] > self.early_band[1] {
+            errors.push(format!("early_band = {:?} : 下限が上限より大きい", self.early_band));
+        }
+        if !(self.mean_yield_rate > 0.0 && self.mean_yield_rate <= 1.0) {
+            errors.push(format!("mean_yield_rate = {} : 0より大きく1以下で指定してください", self.mean_yield_rate));
+        }
+
+        // 2026.10.18 国公立の日程
+        if self.national_round_rates.is_empty() || self.national_round_rates.iter().any(|x| !is_non_negative(*x)) {
+            errors.push(format!("national_round_rates = {:?} : 1個以上の0以上の値で指定してください", self.national_round_rates));
+        } else {
+            // 2026.10.18 日程別の割合の合計は入学定員を超えない。すべて0だと国公立の合格者がいなくなる
+            let total: f64 = self.national_round_rates.iter().sum();
+            if total > 1.0 + 1e-9 {
+                errors.push(format!("national_round_rates = {:?} : 合計 {} が1を超えています", self.national_round_rates, total));
+            } else if total <= 0.0 {
+                errors.push(format!("national_round_rates = {:?} : すべて0です", self.national_round_rates));
+            }
+        }
+        if !self.national_quota_csv.is_empty() && !Path::new(&self.national_quota_csv).is_file() {
+            errors.push(format!("national_quota_csv = {:?} : ファイルがありません", self.national_quota_csv));
+        }
+
+        // 偏差値の範囲
+        if self.national_range[0] > self.national_range[1] {
+            errors.push(format!("national_range = {:?} : 下限が上限より大きい", self.national_range));
+        }
+        for i in 0..self.college_rank_lower.len() {
+            if self.college_rank_lower[i] > self.college_rank_upper[i] {
+                errors.push(format!("college_rank_lower[{0}] = {1} が college_rank_upper[{0}] = {2} より大きい",
+                    i, self.college_rank_lower[i], self.college_rank_upper[i]));
+            }
+        }
+        if self.college_dev_lower > self.college_dev_upper {
+            errors.push(format!("college_dev_lower = {} が college_dev_upper = {} より大きい",
+                self.college_dev_lower, self.college_dev_upper));
+        }
+
+        // 国公立併願時はチャレンジ校の選択数を1減らすので1以上必要
+        for (name, table) in [
+            ("college_rank_select_number", &self.college_rank_select_number),
+            ("college_rank_select_number_diamond", &self.college_rank_select_number_diamond),
+        ].iter() {
+            for (i, row) in table.iter().enumerate() {
+                if row[0] < 1 {
+                    errors.push(format!("{}[{}] = {:?} : チャレンジ校の選択数は1以上で指定してください", name, i, row));
+                }
+            }
+        }
+
+        if self.enroll_algo_version != 1 && self.enroll_algo_version != 2 {
+            errors.push(format!("enroll_algo_version = {} : 1 または 2 で指定してください", self.enroll_algo_version));
+        }
+        if self.policy_window == 0 {
+            errors.push("policy_window = 0 : 1以上で指定してください".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.policy_quantile) {
+            errors.push(format!("policy_quantile = {} : 0以上1以下で指定してください", self.policy_quantile));
+        }
+        if !is_positive(self.policy_target_fill) || self.policy_pid.iter().any(|x| !x.is_finite()) {
+            errors.push(format!("policy_target_fill = {}, policy_pid = {:?} : 目標は正の値、ゲインは有限の値で指定してください",
+                self.policy_target_fill, self.policy_pid));
+        }
+        if !(self.yield_smoothing > 0.0 && self.yield_smoothing <= 1.0) {
+            errors.push(format!("yield_smoothing = {} : 0より大きく1以下で指定してください", self.yield_smoothing));
+        }
+        if !is_positive(self.yield_prior_strength) {
+            errors.push(format!("yield_prior_strength = {} : 正の値で指定してください", self.yield_prior_strength));
+        }
+        if !(self.mean_add_yield_rate > 0.0 && self.mean_add_yield_rate <= 1.0) {
+            errors.push(format!("mean_add_yield_rate = {} : 0より大きく1以下で指定してください", self.mean_add_yield_rate));
+        }
+        if self.new_limits.iter().any(|x| !is_positive(*x)) {
+            errors.push(format!("new_limits = {:?} : 正の値で指定してください", self.new_limits));
+        }
+        if !is_positive(self.sensitivity) {
+            errors.push(format!("sensitivity = {} : 正の値で指定してください", self.sensitivity));
+        }
+
+        if self.geo_model {
+            if !self.student_pref_weights.is_empty() && (self.student_pref_weights.len() != geo::PREF_NUM
+                || self.student_pref_weights.iter().any(|x| !is_non_negative(*x))
+                || !is_positive(self.student_pref_weights.iter().sum())) {
+                errors.push(format!("student_pref_weights : {}個の0以上の値（合計は正）で指定してください", geo::PREF_NUM));
+            }
+            if !is_non_negative(self.geo_distance_scale) {
+                errors.push(format!("geo_distance_scale = {} : 0以上で指定してください", self.geo_distance_scale));
+            }
+            for (name, value) in [("geo_home_bonus", self.geo_home_bonus), ("geo_urban_attraction", self.geo_urban_attraction)].iter() {
+                if !is_positive(*value) {
+                    errors.push(format!("{} = {} : 正の値で指定してください", name, value));
+                }
+            }
+        }
+
+        if !self.resume.is_empty() {
+            if self.replicates > 1 || !self.label.is_empty() {
+                errors.push("--resume は反復実行・スイープ実行では指定できません".to_string());
+            }
+            if !Path::new(&self.resume).is_file() {
+                errors.push(format!("--resume {:?} : ファイルがありません", self.resume));
+            }
+        }
+
+        if self.student_source != StudentSource::Normal {
+            for epoch in 0..self.epochs.max(0) as usize {
+                let path = population::csv_path(self, epoch);
+                if !Path::new(&path).is_file() {
+                    errors.push(format!("student_source : 受験生ファイル {:?} がありません", path));
+                }
+            }
+        }
+
+        if !Path::new(&self.initial_college_csv).is_file() {
+            errors.push(format!("initial_college_csv = {:?} : ファイルがありません", self.initial_college_csv));
+        }
+        if !self.fee_csv.is_empty() && !Path::new(&self.fee_csv).is_file() {
+            errors.push(format!("fee_csv = {:?} : ファイルがありません", self.fee_csv));
+        }
+
+        errors
+    }
+
+    // 2026.10.18 統合後の実効設定をTOMLで出力先Dirに保存
+    fn write_effective(&self) -> Result<()>{
+        let content = toml::to_string(self).context("config cannot serialize")?;
+        fs::write(format!("{}/config.toml", self.output_dir), content)?;
+        Ok(())
+    }
+
+    // 実行前の付帯情報の読み込み
+    fn prepare(&mut self) -> Result<()>{
+        // 2021.11.23 接地用　2年目以降定員情報Vec作成
+        if self.grounding {
+            self.enroll_capa_dics = Config::make_enroll_capa_info(self)?;
+        }
+        // 2026.10.18 国公立の日程別募集人員
+        if !self.national_quota_csv.is_empty() {
+            self.national_quotas = Config::make_national_quotas(self)?;
+        }
+        // 2026.10.18 大学別の入学金・授業料
+        if !self.fee_csv.is_empty() {
+            self.fees = Config::make_fees(self)?;
+        }
+        Ok(())
     }
 
     //データ出力ディレクトリを生成し、その相対パス名を返す。
@@ -196,6 +774,35 @@ impl Config {
         }
     }
 
+    // 2026.10.18 大学別の日程別募集人員を読み込む。CSVにない日程の募集人員は0
+    pub fn make_national_quotas(&self) -> Result<HashMap<Cid, Vec<u32>>>{
+        let rounds = self.national_round_rates.len();
+        let mut h: HashMap<Cid, Vec<u32>> = HashMap::new();
+        let mut rdr = ReaderBuilder::new().from_path(&self.national_quota_csv)
+            .with_context(|| format!("日程別募集人員CSV {:?} を読み込めません", self.national_quota_csv))?;
+        for result in rdr.deserialize(){
+            let q: RoundQuota = result?;
+            if q.round < 1 || q.round > rounds {
+                return Err(anyhow!("日程別募集人員CSV {:?}: cid = {} の round = {} は1から{}で指定してください",
+                    self.national_quota_csv, q.cid, q.round, rounds))
+            }
+            h.entry(q.cid).or_insert_with(|| vec![0; rounds])[q.round - 1] = q.quota;
+        }
+        Ok(h)
+    }
+
+    // 2026.10.18 大学別の入学金・授業料を読み込む
+    pub fn make_fees(&self) -> Result<HashMap<Cid, (u64, u64)>>{
+        let mut h: HashMap<Cid, (u64, u64)> = HashMap::new();
+        let mut rdr = ReaderBuilder::new().from_path(&self.fee_csv)
+            .with_context(|| format!("入学金・授業料CSV {:?} を読み込めません", self.fee_csv))?;
+        for result in rdr.deserialize(){
+            let f: CollegeFee = result?;
+            h.insert(f.cid, (f.entrance_fee, f.tuition));
+        }
+        Ok(h)
+    }
+
     // 2021.11.23 定員情報作成
     pub fn make_enroll_capa_info(&self) -> Result<Vec<HashMap<usize, (i32,i32)>>>{
         let mut v = vec![];
@@ -203,7 +810,8 @@ impl Config {
             let mut h = HashMap::new();
             let year = self.start_year + i as usize;
             let path = format!("{}{:04}{}", self.enroll_capa_csv_dir, year, self.enroll_capa_csv_name);
-            let mut rdr = ReaderBuilder::new().from_path(path)?;
+            let mut rdr = ReaderBuilder::new().from_path(&path)
+                .with_context(|| format!("定員情報CSV {:?} を読み込めません", path))?;
             for result in rdr.deserialize(){
                 let e: EnrollAndCapa = result?;
                 h.insert(e.cid, (e.enroll, e.capa));
@@ -212,4 +820,93 @@ impl Config {
         }
         Ok(v)
     }
-}
\ No newline at end of file
+}
+
+// 2026.10.18 設定ファイルを読み込み、extendsで指定された継承元を再帰的に統合したテーブルを返す。
+// 継承元は指定したファイルからの相対パス。子の項目が親の項目を上書きする。
+pub fn load_table(filename: &Path) -> Result<Table>{
+    load_table_chain(filename, &mut Vec::new())
+}
+
+// chain: 継承をたどってきたファイル。循環検出用
+fn load_table_chain(filename: &Path, chain: &mut Vec<PathBuf>) -> Result<Table>{
+    let canonical = fs::canonicalize(filename)
+        .with_context(|| format!("設定ファイル {:?} を読み込めません", filename))?;
+    if chain.contains(&canonical) {
+        let names: Vec<String> = chain.iter().chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(anyhow!("extends が循環しています: {}", names.join(" -> ")))
+    }
+
+    let contents = fs::read_to_string(filename)
+        .with_context(|| format!("設定ファイル {:?} を読み込めません", filename))?;
+    let mut table: Table = toml::from_str(&contents)
+        .with_context(|| format!("設定ファイル {:?} のTOML形式が正しくありません", filename))?;
+
+    match table.remove(EXTENDS_KEY){
+        None => Ok(table),
+        Some(toml::Value::String(parent)) => {
+            let parent = filename.parent().unwrap_or_else(|| Path::new("")).join(parent);
+            eprintln!("    継承元 = {:?}", parent);
+            chain.push(canonical);
+            let mut merged = load_table_chain(&parent, chain)?;
+            for (k, v) in table {
+                merged.insert(k, v);
+            }
+            Ok(merged)
+        },
+        Some(v) => Err(anyhow!("{:?} の extends = {} : ファイル名で指定してください", filename, v)),
+    }
+}
+
+// 2026.10.18 --set key=value を(項目名, 値)のリストにする。値はTOMLとして解釈できなければ文字列
+// 項目名と型は既定の設定に当てはめて検査
+fn parse_sets(matches: &ArgMatches) -> Result<Vec<(String, toml::Value)>>{
+    let sets = match matches.values_of("set") {
+        Some(sets) => sets,
+        None => return Ok(Vec::new()),
+    };
+    let defaults = match toml::Value::try_from(Config::default())? {
+        toml::Value::Table(t) => t,
+        _ => return Err(anyhow!("default config must be a table")),
+    };
+
+    let mut parsed = Vec::new();
+    for s in sets {
+        let (key, raw) = match s.find('=') {
+            Some(i) => (s[..i].trim(), s[i + 1..].trim()),
+            None => return Err(anyhow!("--set {:?} は key=value の形式で指定してください", s)),
+        };
+        let value = toml::from_str::<Table>(&format!("v = {}", raw)).ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+        let mut table = defaults.clone();
+        table.insert(key.to_string(), value.clone());
+        let mut ignored: Vec<String> = Vec::new();
+        serde_ignored::deserialize::<_, _, Config>(toml::Value::Table(table), |path| ignored.push(path.to_string()))
+            .with_context(|| format!("--set {}: 値 {} の型が正しくありません", key, raw))?;
+        if ignored.iter().any(|k| k == key) {
+            return Err(anyhow!("--set {}: 不明な設定項目です", key));
+        }
+        parsed.push((key.to_string(), value));
+    }
+    Ok(parsed)
+}
+
+// --setの値で設定テーブルを上書き
+fn apply_sets(table: &mut Table, sets: &[(String, toml::Value)]){
+    for (k, v) in sets.iter() {
+        table.insert(k.clone(), v.clone());
+    }
+}
+
+// 正の有限値か。NaNはfalse
+fn is_positive(x: f64) -> bool{
+    x > 0.0 && x.is_finite()
+}
+
+fn is_non_negative(x: f64) -> bool{
+    x >= 0.0 && x.is_finite()
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+// 2026.10.18 大学による歩留率の学習
+//   ewma 指数平滑 推定値 = yield_smoothing x 今年度の歩留率 + (1 - yield_smoothing) x 前年度の推定値
+//   beta ベータ・二項モデル 事前分布 Beta(平均 x 強さ, (1 - 平均) x 強さ) を全年度の入学者数・合格者数で更新
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YieldEstimator {
+    #[default]
+    Ewma,
+    Beta,
+}
+
+// 推定値の下限。合格者数の計算で0割りにならないように
+const MIN_YIELD: f64 = 0.01;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct YieldEstimate{
+    pub mean: f64, //歩留率の推定値
+    pub alpha: f64, //beta：事後分布のパラメータ（事前＋入学者数の累計）
+    pub beta: f64, //beta：事後分布のパラメータ（事前＋辞退者数の累計）
+}
+
+impl YieldEstimate {
+    // 事前の推定値から作成
+    pub fn new(conf: &Config, prior: f64) -> Self{
+        Self{
+            mean: prior,
+            alpha: prior * conf.yield_prior_strength,
+            beta: (1.0 - prior) * conf.yield_prior_strength,
+        }
+    }
+
+    // 1年分の入学者数・合格者数で更新。合格者がいなければ更新しない
+    pub fn update(&mut self, conf: &Config, admitted: u32, passed: u32){
+        if passed == 0 {
+            return
+        }
+        let admitted = admitted.min(passed);
+        match conf.yield_estimator {
+            YieldEstimator::Ewma => {
+                let observed = admitted as f64 / passed as f64;
+                self.mean = conf.yield_smoothing * observed + (1.0 - conf.yield_smoothing) * self.mean;
+            },
+            YieldEstimator::Beta => {
+                self.alpha += admitted as f64;
+                self.beta += (passed - admitted) as f64;
+                self.mean = self.alpha / (self.alpha + self.beta);
+            },
+        }
+    }
+
+    // 合格者数の計算に使う歩留率
+    pub fn rate(&self) -> f64{
+        self.mean.max(MIN_YIELD)
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{anyhow, Result};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand_xoshiro::Xoshiro256StarStar;
+
+use crate::college::College;
+use crate::config::Config;
+
+// 2026.10.18 地理モデル
+// 受験生に出身都道府県を割り当て、大学選択の重みに距離・地元・都市圏の効果を掛ける。
+
+// 都市区分表記（College.urban）
+pub const URBAN: &str = "都市圏";
+
+pub const PREF_NUM: usize = 47;
+
+// 距離減衰の下限。遠方の大学の重みが0になると選択数を満たせなくなるため
+const MIN_DECAY: f64 = 1e-6;
+
+// 都道府県番号順（1-47）の 名称, 県庁所在地の緯度, 経度, 人口（2020年国勢調査 万人 概数）
+pub const PREFECTURES: [(&str, f64, f64, f64); PREF_NUM] = [
+    ("北海道", 43.064, 141.347, 522.0),
+    ("青森県", 40.824, 140.740, 124.0),
+    ("岩手県", 39.704, 141.153, 121.0),
+    ("宮城県", 38.269, 140.872, 230.0),
+    ("秋田県", 39.719, 140.102, 96.0),
+    ("山形県", 38.240, 140.364, 107.0),
+    ("福島県", 37.750, 140.468, 183.0),
+    ("茨城県", 36.342, 140.447, 287.0),
+    ("栃木県", 36.566, 139.884, 193.0),
+    ("群馬県", 36.391, 139.061, 194.0),
+    ("埼玉県", 35.857, 139.649, 734.0),
+    ("千葉県", 35.605, 140.123, 628.0),
+    ("東京都", 35.690, 139.692, 1405.0),
+    ("神奈川県", 35.448, 139.643, 924.0),
+    ("新潟県", 37.902, 139.024, 220.0),
+    ("富山県", 36.695, 137.211, 103.0),
+    ("石川県", 36.595, 136.626, 113.0),
+    ("福井県", 36.065, 136.222, 77.0),
+    ("山梨県", 35.664, 138.568, 81.0),
+    ("長野県", 36.651, 138.181, 205.0),
+    ("岐阜県", 35.391, 136.722, 198.0),
+    ("静岡県", 34.977, 138.383, 363.0),
+    ("愛知県", 35.180, 136.907, 754.0),
+    ("三重県", 34.730, 136.509, 177.0),
+    ("滋賀県", 35.004, 135.869, 141.0),
+    ("京都府", 35.021, 135.756, 258.0),
+    ("大阪府", 34.686, 135.520, 884.0),
+    ("兵庫県", 34.691, 135.183, 547.0),
+    ("奈良県", 34.685, 135.833, 132.0),
+    ("和歌山県", 34.226, 135.168, 92.0),
+    ("鳥取県", 35.504, 134.238, 55.0),
+    ("島根県", 35.472, 133.051, 67.0),
+    ("岡山県", 34.662, 133.935, 189.0),
+    ("広島県", 34.396, 132.459, 280.0),
+    ("山口県", 34.186, 131.471, 134.0),
+    ("徳島県", 34.066, 134.559, 72.0),
+    ("香川県", 34.340, 134.043, 95.0),
+    ("愛媛県", 33.842, 132.766, 133.0),
+    ("高知県", 33.560, 133.531, 69.0),
+    ("福岡県", 33.607, 130.418, 514.0),
+    ("佐賀県", 33.249, 130.299, 81.0),
+    ("長崎県", 32.745, 129.874, 131.0),
+    ("熊本県", 32.790, 130.742, 174.0),
+    ("大分県", 33.238, 131.613, 112.0),
+    ("宮崎県", 31.911, 131.424, 107.0),
+    ("鹿児島県", 31.560, 130.558, 159.0),
+    ("沖縄県", 26.212, 127.681, 147.0),
+];
+
+// 都道府県番号（1-47）が正しいか
+pub fn is_pref(pref: u8) -> bool{
+    pref >= 1 && pref as usize <= PREF_NUM
+}
+
+pub fn pref_name(pref: u8) -> &'static str{
+    if is_pref(pref) { PREFECTURES[pref as usize - 1].0 } else { "" }
+}
+
+// 県庁所在地間の大圏距離（km）。都道府県番号が不明なら None
+pub fn distance_km(from: u8, to: u8) -> Option<f64>{
+    if !is_pref(from) || !is_pref(to) {
+        return None
+    }
+    let (_, lat1, lon1, _) = PREFECTURES[from as usize - 1];
+    let (_, lat2, lon2, _) = PREFECTURES[to as usize - 1];
+    let (lat1, lon1, lat2, lon2) = (lat1.to_radians(), lon1.to_radians(), lat2.to_radians(), lon2.to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    Some(2.0 * 6371.0 * h.sqrt().asin())
+}
+
+// 出身都道府県の分布。student_pref_weightsが空なら人口比
+pub fn pref_dist(conf: &Config) -> Result<WeightedIndex<f64>>{
+    let weights: Vec<f64> = if conf.student_pref_weights.is_empty() {
+        PREFECTURES.iter().map(|p| p.3).collect()
+    } else {
+        conf.student_pref_weights.clone()
+    };
+    WeightedIndex::new(weights).map_err(|e| anyhow!("student_pref_weights が正しくありません: {}", e))
+}
+
+// 出身都道府県番号を抽選
+pub fn draw_pref(dist: &WeightedIndex<f64>, rng: &mut Xoshiro256StarStar) -> u8{
+    (dist.sample(rng) + 1) as u8
+}
+
+// 受験生の出身都道府県から見た大学選択の倍率
+//   距離減衰 exp(-距離 / geo_distance_scale)、地元 geo_home_bonus倍、都市圏 geo_urban_attraction倍
+pub fn select_factor(conf: &Config, home: u8, college: &College) -> f64{
+    let mut factor = 1.0;
+    if let Some(d) = distance_km(home, college.pref) {
+        if conf.geo_distance_scale > 0.0 {
+            factor *= (-d / conf.geo_distance_scale).exp().max(MIN_DECAY);
+        }
+        if home == college.pref {
+            factor *= conf.geo_home_bonus;
+        }
+    }
+    if college.urban == URBAN {
+        factor *= conf.geo_urban_attraction;
+    }
+    factor
+}
@@ -0,0 +1,21 @@
+pub mod college;
+pub mod student;
+pub mod population;
+pub mod geo;
+pub mod aggregate;
+pub mod config;
+pub mod simulation;
+pub mod output;
+pub mod replicate;
+pub mod sweep;
+pub mod sink;
+pub mod checkpoint;
+pub mod policy;
+pub mod estimator;
+
+use sprs::CsMatBase;
+
+pub use crate::simulation::{Simulation, run};
+
+pub type Matrix = CsMatBase<u8, usize, Vec<usize>, Vec<usize>, Vec<u8>, usize>;
+pub type SidStatus = (usize, u8);
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::college::{College, Cid, CollegeResult};
+use crate::student::StudentResult;
+use crate::config::Config;
+use crate::sink::create_sink;
+use crate::aggregate::aggregate;
+
+//シミュレーション結果を出力
+pub fn output_result(conf: &Config, epoch: i32, college_results: &[CollegeResult], student_results: &[StudentResult]) -> Result<()>{
+    //大学側結果を出力 2026.10.18 形式は設定で選択。拡張子は形式に合わせる
+    let path = format!("{}/college{:02}", conf.output_dir, epoch);
+    let mut sink = create_sink(conf.college_output_format, Path::new(&path))?;
+    sink.write_all(college_results)?;
+    sink.finish()?;
+
+    // 2026.10.18 設置区分・都市区分・都道府県・大学規模別の集計結果
+    let path = format!("{}/aggregate{:02}", conf.output_dir, epoch);
+    let mut sink = create_sink(conf.college_output_format, Path::new(&path))?;
+    sink.write_all(&aggregate(epoch, college_results))?;
+    sink.finish()?;
+
+    //学生側結果を指定フォルダーに保存
+    let path = format!("{}/student{:02}", conf.output_dir, epoch);
+    let mut sink = create_sink(conf.student_output_format, Path::new(&path))?;
+    sink.write_all(student_results)?;
+    sink.finish()?;
+
+    Ok(())
+}
+
+// 2026.10.18 履歴の出力形式
+//   stdout 従来どおり大学エージェントのJSONを標準出力へ（既定。スイープ実行時はhistory.json）
+//   json   大学エージェントのJSONを出力先Dirのhistory.jsonへ
+//   csv    大学 x エポック 1行の縦持ちCSVを出力先Dirのhistory.csvへ
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryFormat {
+    #[default]
+    Stdout,
+    Json,
+    Csv,
+}
+
+// 2026.10.18 履歴CSV 1行 = 大学 x エポック。epoch = -1 はシミュレーション前の初期値
+#[derive(Debug, Serialize)]
+struct HistoryRow<'a>{
+    cid: Cid, //旺文社の大学番号
+    name: &'a str,  //  大学名
+    institute: u8, // 設置区分：1国立 2公立 3私立
+    epoch: i32, //エポック数
+    dev: f64, // 偏差値
+    admissions: i32, //入学者数
+    fill_rate: f64, //入学定員充足率
+    yield_est: f64, //2026.10.18 一次合格歩留率の推定値
+    add_yield_est: f64, //2026.10.18 追加合格歩留率の推定値
+    seed: u64, //ランダムシード
+    scenario: i32, //シナリオ番号
+}
+
+//シミュレーション結果を出力 　最終の大学エージェント（偏差値と入学定員充足率の履歴付き）を保存
+pub fn output_history(conf: &Config, colleges: &[College]) -> Result<()>{
+    match conf.history_format {
+        HistoryFormat::Stdout if conf.label.is_empty() => {
+            let content = serde_json::to_string_pretty(&colleges)?;
+            println!("{}", content);
+        },
+        HistoryFormat::Stdout | HistoryFormat::Json => {
+            let content = serde_json::to_string_pretty(&colleges)?;
+            fs::write(format!("{}/history.json", conf.output_dir), content)?;
+        },
+        HistoryFormat::Csv => {
+            let path = format!("{}/history.csv", conf.output_dir);
+            let mut wtr = csv::Writer::from_path(path)?;
+            for c in colleges{
+                let steps = c.dev_history.iter()
+                    .zip(c.adm_history.iter())
+                    .zip(c.fillrate_history.iter())
+                    .zip(c.yield_est_history.iter().zip(c.add_yield_est_history.iter()));
+                for (i, (((dev, adm), fill_rate), (yield_est, add_yield_est))) in steps.enumerate(){
+                    wtr.serialize(HistoryRow{
+                        cid: c.cid,
+                        name: &c.name,
+                        institute: c.institute,
+                        epoch: i as i32 - 1,
+                        dev: *dev,
+                        admissions: *adm,
+                        fill_rate: *fill_rate,
+                        yield_est: *yield_est,
+                        add_yield_est: *add_yield_est,
+                        seed: c.seed,
+                        scenario: c.senario,
+                    })?;
+                }
+            }
+            wtr.flush()?;
+        },
+    }
+
+    Ok(())
+}
+
+// 2026.10.18 実行記録 run.json
+#[derive(Debug, Serialize)]
+struct RunInfo<'a>{
+    version: &'a str, //examsimのバージョン
+    config_file: &'a str, //設定ファイル名
+    label: &'a str, //スイープ実行時のラベル
+    cli_overrides: &'a [String], //コマンドライン引数による上書き
+    random_seed: u64,
+    started_at: String,
+    finished_at: String,
+    elapsed_secs: f64,
+    config: &'a Config, //統合後の実効設定
+}
+
+// 2026.10.18 実効設定と実行メタデータを出力先Dirに保存
+pub fn output_run_info(conf: &Config, started_at: &DateTime<Local>, timer: &Instant) -> Result<()>{
+    let info = RunInfo{
+        version: env!("CARGO_PKG_VERSION"),
+        config_file: &conf.config_file,
+        label: &conf.label,
+        cli_overrides: &conf.cli_overrides,
+        random_seed: conf.random_seed,
+        started_at: started_at.to_rfc3339(),
+        finished_at: Local::now().to_rfc3339(),
+        elapsed_secs: timer.elapsed().as_secs_f64(),
+        config: conf,
+    };
+    let content = serde_json::to_string_pretty(&info)?;
+    fs::write(format!("{}/run.json", conf.output_dir), content)?;
+    Ok(())
+}
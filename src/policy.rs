@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::college::College;
+use crate::config::Config;
+
+// 2026.10.18 大学の一次合格者数の決め方
+//   fixed    入学定員そのまま（国公立と同じ）
+//   v1       前年度の合格者超過率 x 入学定員超過率制限値の増減率（enroll_algo_version = 1）
+//   v2       前年度の歩留率と受験者数の変化率（enroll_algo_version = 2）
+//   average  直近 policy_window 年の歩留率の平均
+//   quantile 歩留率の履歴の policy_quantile 分位点。高めの歩留率を見込んで定員超過を避ける
+//   pid      v2の合格者数を定員充足率の目標値との差でPID制御
+//   adaptive 全年度の実績から学習した歩留率の推定値（yield_estimator）。追加合格用人数も追加合格の歩留率の推定値で補正
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyKind {
+    Fixed,
+    V1,
+    V2,
+    Average,
+    Quantile,
+    Pid,
+    Adaptive,
+}
+
+impl PolicyKind {
+    // enroll_algo_versionに対応する方式
+    pub fn from_version(version: i32) -> Self{
+        if version == 2 { PolicyKind::V2 } else { PolicyKind::V1 }
+    }
+}
+
+// 合格者数の計算に使う今年度の情報
+pub struct EnrollContext{
+    pub applicate_num: usize, //今年度受験者数
+    pub current_rate: f64, //今年度の入学定員超過率制限値
+    pub before_rate: f64, //前年度の入学定員超過率制限値
+}
+
+pub trait EnrollmentPolicy {
+    // 今年度の一次合格者数
+    fn enroll_num(&self, conf: &Config, college: &College, ctx: &EnrollContext) -> usize;
+}
+
+pub struct Fixed;
+pub struct OverRate;
+pub struct YieldRate;
+pub struct AverageYield;
+pub struct QuantileYield;
+pub struct FillRatePid;
+pub struct AdaptiveYield;
+
+pub fn policy(kind: PolicyKind) -> &'static dyn EnrollmentPolicy{
+    match kind {
+        PolicyKind::Fixed => &Fixed,
+        PolicyKind::V1 => &OverRate,
+        PolicyKind::V2 => &YieldRate,
+        PolicyKind::Average => &AverageYield,
+        PolicyKind::Quantile => &QuantileYield,
+        PolicyKind::Pid => &FillRatePid,
+        PolicyKind::Adaptive => &AdaptiveYield,
+    }
+}
+
+impl EnrollmentPolicy for Fixed {
+    fn enroll_num(&self, _conf: &Config, college: &College, _ctx: &EnrollContext) -> usize{
+        college.enroll as usize
+    }
+}
+
+impl EnrollmentPolicy for OverRate {
+    fn enroll_num(&self, _conf: &Config, college: &College, ctx: &EnrollContext) -> usize{
+        // 2016(0)以前と2016(1)の増減率を取得。
+        let limit_change_rate = ctx.current_rate / ctx.before_rate;
+        (college.enroll as f64 * college.over_rate * limit_change_rate).round() as usize
+    }
+}
+
+impl EnrollmentPolicy for YieldRate {
+    fn enroll_num(&self, conf: &Config, college: &College, ctx: &EnrollContext) -> usize{
+        // 歩留率計算．入学者数または合格者数が欠損（0）の場合は2014年度の私立大学平均を使う
+        let yield_rate = match college.yield_rate() {
+            x if x > 0.0 => x,
+            _ => conf.mean_yield_rate,
+        };
+        by_yield_rate(conf, college, ctx, yield_rate)
+    }
+}
+
+impl EnrollmentPolicy for AverageYield {
+    fn enroll_num(&self, conf: &Config, college: &College, ctx: &EnrollContext) -> usize{
+        let yields = yield_samples(college);
+        let recent = &yields[yields.len().saturating_sub(conf.policy_window)..];
+        let yield_rate = if recent.is_empty() {
+            conf.mean_yield_rate
+        } else {
+            recent.iter().sum::<f64>() / recent.len() as f64
+        };
+        by_yield_rate(conf, college, ctx, yield_rate)
+    }
+}
+
+impl EnrollmentPolicy for QuantileYield {
+    fn enroll_num(&self, conf: &Config, college: &College, ctx: &EnrollContext) -> usize{
+        let mut yields = yield_samples(college);
+        let yield_rate = if yields.is_empty() {
+            conf.mean_yield_rate
+        } else {
+            yields.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            // 線形補間した分位点
+            let pos = conf.policy_quantile * (yields.len() - 1) as f64;
+            let (lower, upper) = (pos.floor() as usize, pos.ceil() as usize);
+            yields[lower] + (yields[upper] - yields[lower]) * (pos - lower as f64)
+        };
+        by_yield_rate(conf, college, ctx, yield_rate)
+    }
+}
+
+impl EnrollmentPolicy for FillRatePid {
+    fn enroll_num(&self, conf: &Config, college: &College, ctx: &EnrollContext) -> usize{
+        let base = YieldRate.enroll_num(conf, college, ctx) as f64;
+        // 誤差 = 目標充足率 - 各年度の充足率。fillrate_historyの先頭はシミュレーション前の値なので除く
+        let errors: Vec<f64> = college.fillrate_history.iter()
+            .skip(1)
+            .map(|x| conf.policy_target_fill - x)
+            .collect();
+        let [kp, ki, kd] = conf.policy_pid;
+        let control = if let Some(last) = errors.last() {
+            let prev = if errors.len() >= 2 { errors[errors.len() - 2] } else { *last };
+            kp * last + ki * errors.iter().sum::<f64>() + kd * (last - prev)
+        } else {
+            0.0
+        };
+        (base * (1.0 + control).max(0.0)).round() as usize
+    }
+}
+
+impl EnrollmentPolicy for AdaptiveYield {
+    fn enroll_num(&self, conf: &Config, college: &College, ctx: &EnrollContext) -> usize{
+        by_yield_rate(conf, college, ctx, college.yield_est.rate())
+    }
+}
+
+// 歩留率から合格者数を計算（v2と同じ）
+fn by_yield_rate(conf: &Config, college: &College, ctx: &EnrollContext, yield_rate: f64) -> usize{
+    let enroll =  college.enroll as f64 * ctx.current_rate / yield_rate;
+    // 2021.12.31 2年目以降は前年度受験者数と今回受験者数の変化率で補正する
+    let apply_change_rate = if college.epoch == 0 { 1_f64 }else{
+        college.applicate_num as f64 / ctx.applicate_num  as f64
+    };
+    // 2022.01.01 超過率に対する感度
+    (enroll *  apply_change_rate * conf.sensitivity) as usize
+}
+
+// 歩留率の履歴のうち有効な値（欠損は0で記録）
+fn yield_samples(college: &College) -> Vec<f64>{
+    college.yield_history.iter().copied().filter(|x| *x > 0.0).collect()
+}
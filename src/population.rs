@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Context, Result};
+use csv::ReaderBuilder;
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand_distr::{Normal, StandardNormal};
+use rand_xoshiro::Xoshiro256StarStar;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+// 2026.10.18 受験生母集団の作り方
+//   normal    正規分布 N(student_dev_mu, student_dev_sigma^2) から生成（従来）
+//   csv       エポック別の受験生CSVをそのまま使う。人数はCSVの行数
+//   histogram エポック別の偏差値ヒストグラムCSVから student_number 人を再標本化
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StudentSource {
+    #[default]
+    Normal,
+    Csv,
+    Histogram,
+}
+
+// 2026.10.18 正規分布以外の偏差値分布（student_source = "normal" のとき）
+//   normal     正規分布
+//   skewnormal 歪正規分布。平均・標準偏差は student_dev_mu, student_dev_sigma のまま、歪みを student_dev_skew で指定
+//   mixture    正規分布の混合。student_dev_mixture = [[重み, 平均, 標準偏差], ...]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StudentDist {
+    #[default]
+    Normal,
+    SkewNormal,
+    Mixture,
+}
+
+// 偏差値の分布。エポック別の平均・標準偏差を反映済み
+pub enum AbilityDist{
+    Normal(Normal<f64>),
+    SkewNormal{ loc: f64, scale: f64, delta: f64 },
+    Mixture{ weights: WeightedIndex<f64>, components: Vec<Normal<f64>> },
+}
+
+impl AbilityDist {
+    pub fn from_conf(conf: &Config, epoch: usize) -> Result<Self>{
+        let (mu, sigma) = conf.student_dev_params(epoch);
+        Ok(match conf.student_dist {
+            StudentDist::Normal => AbilityDist::Normal(Normal::new(mu, sigma)?),
+            // Azzalini (1985)。形状パラメータaから位置・尺度を平均・標準偏差に合わせて逆算する
+            StudentDist::SkewNormal => {
+                let a = conf.student_dev_skew;
+                let delta = a / (1.0 + a * a).sqrt();
+                let scale = sigma / (1.0 - 2.0 * delta * delta / std::f64::consts::PI).sqrt();
+                let loc = mu - scale * delta * (2.0 / std::f64::consts::PI).sqrt();
+                AbilityDist::SkewNormal{ loc, scale, delta }
+            },
+            StudentDist::Mixture => {
+                let weights = WeightedIndex::new(conf.student_dev_mixture.iter().map(|c| c[0]))
+                    .map_err(|e| anyhow!("student_dev_mixture の重みが正しくありません: {}", e))?;
+                let components = conf.student_dev_mixture.iter()
+                    .map(|c| Normal::new(c[1], c[2]))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                AbilityDist::Mixture{ weights, components }
+            },
+        })
+    }
+}
+
+impl Distribution<f64> for AbilityDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64{
+        match self {
+            AbilityDist::Normal(normal) => normal.sample(rng),
+            AbilityDist::SkewNormal{ loc, scale, delta } => {
+                let u0: f64 = rng.sample(StandardNormal);
+                let v: f64 = rng.sample(StandardNormal);
+                let u1 = delta * u0 + (1.0 - delta * delta).sqrt() * v;
+                loc + scale * if u0 >= 0.0 { u1 } else { -u1 }
+            },
+            AbilityDist::Mixture{ weights, components } => components[weights.sample(rng)].sample(rng),
+        }
+    }
+}
+
+// 切断：範囲外の値は引き直す。範囲内の確率が極端に小さい設定で止まらないよう回数に上限を設ける
+const TRUNCATE_MAX_TRIES: usize = 10_000;
+
+fn sample_truncated(dist: &AbilityDist, bounds: [f64; 2], rng: &mut Xoshiro256StarStar) -> Result<f64>{
+    for _ in 0..TRUNCATE_MAX_TRIES {
+        let x = dist.sample(rng);
+        if x >= bounds[0] && x <= bounds[1] {
+            return Ok(x)
+        }
+    }
+    Err(anyhow!("student_dev_bounds = {:?} の範囲の偏差値が生成できません", bounds))
+}
+
+// 受験生CSV 1行 = 受験生1人
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StudentRecord{
+    #[serde(default)]
+    pub id: Option<usize>, //元データの受験生番号。シミュレーション内では偏差値順の連番を振り直す
+    #[serde(alias = "偏差値")]
+    pub score: f64, //偏差値
+    #[serde(default)]
+    pub pattern: Option<u8>, //出願パターン 1:国公立私立併願 2:私立専願。空欄ならnational_probで決める
+    #[serde(default)]
+    pub pref: Option<u8>, //都道府県番号：1-47
+    #[serde(default)]
+    pub ronin: u32, //2026.10.18 浪人年数。0は現役
+    #[serde(default)]
+    pub budget: Option<u64>, //2026.10.18 入学金に充てられる予算（円）。空欄ならbudget_model有効時に生成
+}
+
+impl StudentRecord {
+    pub fn from_score(score: f64) -> Self{
+        Self{ score, ..Default::default() }
+    }
+}
+
+// 偏差値ヒストグラムCSV 1行 = 1階級。階級内は一様分布とする
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistogramBin{
+    pub lower: f64, //階級の下限（含む）
+    pub upper: f64, //階級の上限（含まない）
+    pub count: f64, //度数または相対度数
+}
+
+// エポック別の受験生CSV・ヒストグラムCSVのパス。ファイル名は 年度4桁 + student_csv_name
+pub fn csv_path(conf: &Config, epoch: usize) -> String{
+    format!("{}{:04}{}", conf.student_csv_dir, conf.start_year + epoch, conf.student_csv_name)
+}
+
+// 今回のエポックの受験生の偏差値（と指定があれば出願パターン・都道府県）を作成
+pub fn draw(conf: &Config, epoch: usize, rng: &mut Xoshiro256StarStar) -> Result<Vec<StudentRecord>>{
+    match conf.student_source {
+        StudentSource::Normal => {
+            let dist = AbilityDist::from_conf(conf, epoch)?;
+            let num = conf.student_number[epoch]; //今回の年度の志願者数分生成
+            if conf.student_dev_truncate {
+                (0..num)
+                    .map(|_| sample_truncated(&dist, conf.student_dev_bounds, rng).map(StudentRecord::from_score))
+                    .collect()
+            } else {
+                Ok(dist.sample_iter(rng)
+                    .take(num)
+                    .map(StudentRecord::from_score)
+                    .collect())
+            }
+        },
+        StudentSource::Csv => read_students(&csv_path(conf, epoch)),
+        StudentSource::Histogram => {
+            let bins = read_histogram(&csv_path(conf, epoch))?;
+            sample_histogram(&bins, conf.student_number[epoch], rng)
+        },
+    }
+}
+
+// 2026.10.18 今回のエポックの受験生の偏差値の平均と標準偏差。ダイヤモンド型受験の判定と試験の誤差に使う
+// 設定値が母集団の平均・標準偏差になる分布（normal、skewnormal）は設定値、
+// それ以外（mixture、切断、受験生CSV、ヒストグラム）は生成した偏差値の平均と標準偏差
+pub fn dev_params(conf: &Config, epoch: usize, records: &[StudentRecord]) -> (f64, f64){
+    let by_conf = conf.student_source == StudentSource::Normal
+        && conf.student_dist != StudentDist::Mixture && !conf.student_dev_truncate;
+    if by_conf || records.is_empty() {
+        return conf.student_dev_params(epoch)
+    }
+    let n = records.len() as f64;
+    let mean = records.iter().map(|r| r.score).sum::<f64>() / n;
+    let var = records.iter().map(|r| (r.score - mean).powi(2)).sum::<f64>() / n;
+    (mean, var.sqrt())
+}
+
+pub fn read_students(path: &str) -> Result<Vec<StudentRecord>>{
+    let mut rdr = ReaderBuilder::new().from_path(path)
+        .with_context(|| format!("受験生CSV {:?} を読み込めません", path))?;
+    let mut records: Vec<StudentRecord> = Vec::new();
+    for (i, result) in rdr.deserialize().enumerate(){
+        let r: StudentRecord = result.with_context(|| format!("受験生CSV {:?} {}行目", path, i + 2))?;
+        if let Some(p) = r.pattern {
+            if p != 1 && p != 2 {
+                return Err(anyhow!("受験生CSV {:?} {}行目: pattern = {} は 1 または 2 で指定してください", path, i + 2, p))
+            }
+        }
+        records.push(r);
+    }
+    Ok(records)
+}
+
+pub fn read_histogram(path: &str) -> Result<Vec<HistogramBin>>{
+    let mut rdr = ReaderBuilder::new().from_path(path)
+        .with_context(|| format!("ヒストグラムCSV {:?} を読み込めません", path))?;
+    let mut bins: Vec<HistogramBin> = Vec::new();
+    for result in rdr.deserialize(){
+        let b: HistogramBin = result?;
+        if b.lower >= b.upper || b.count < 0.0 {
+            return Err(anyhow!("ヒストグラムCSV {:?}: 階級 {:?} が正しくありません", path, b))
+        }
+        bins.push(b);
+    }
+    Ok(bins)
+}
+
+// ヒストグラムから度数に比例した確率で階級を選び、階級内の一様乱数で偏差値を作る
+pub fn sample_histogram(bins: &[HistogramBin], num: usize, rng: &mut Xoshiro256StarStar) -> Result<Vec<StudentRecord>>{
+    let dist = WeightedIndex::new(bins.iter().map(|b| b.count))
+        .map_err(|e| anyhow!("ヒストグラムの度数が正しくありません: {}", e))?;
+    Ok((0..num)
+        .map(|_| {
+            let b = &bins[dist.sample(rng)];
+            StudentRecord::from_score(rng.gen_range(b.lower..b.upper))
+        })
+        .collect())
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::Instant;
+use anyhow::Result;
+use chrono::Local;
+use serde::Serialize;
+
+use crate::college::{College, Cid, CollegeResult};
+use crate::config::Config;
+use crate::output::output_run_info;
+use crate::simulation::Simulation;
+
+// 反復集計する指標名。CollegeResultの項目名に合わせる。
+const METRICS: [&str; 4] = ["admissons", "new_deviation", "fill_rate", "apply_count"];
+
+// 反復集計結果CSV 1行 = エポック x 大学 x 指標
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplicateSummary{
+    pub epoch: i32, //エポック数
+    pub cid: Cid, //旺文社の大学番号
+    pub name: String,  //  大学名
+    pub institute: u8, // 設置区分：1国立 2公立 3私立
+    pub metric: String, //指標名
+    pub n: usize, //有効サンプル数（入学者0で偏差値が求まらない回は除く）
+    pub mean: f64, //平均
+    pub std: f64, //標準偏差（不偏）
+    pub min: f64,
+    pub q05: f64,
+    pub q25: f64,
+    pub q50: f64,
+    pub q75: f64,
+    pub q95: f64,
+    pub max: f64,
+}
+
+// 大学別・エポック別の指標サンプル
+struct Samples{
+    name: String,
+    institute: u8,
+    values: [Vec<f64>; 4], // METRICSの順
+}
+
+// random_seedから1ずつ変えたシードでconf.replicates回シミュレーションを実行し、
+// 大学別・エポック別の統計量をreplicates.csvに出力する。
+// 大学初期値と接地用定員情報は読み込み済みのものを使い回す。
+pub fn run(conf: &Config, colleges: &[College], timer: &Instant) -> Result<()>{
+    let mut samples: BTreeMap<(i32, Cid), Samples> = BTreeMap::new();
+
+    for r in 0..conf.replicates {
+        let mut rep_conf = conf.clone();
+        rep_conf.random_seed = conf.random_seed + r as u64;
+        eprintln!("  replicate[{:03}]:seed={} \t{:?}", r, rep_conf.random_seed, timer.elapsed());
+
+        // ログ出力・チェックポイント保存時はシード別のサブフォルダーに出力
+        if conf.logging || conf.checkpoint {
+            rep_conf.output_dir = format!("{}r{:04}/", conf.output_dir, rep_conf.random_seed);
+            fs::create_dir(&rep_conf.output_dir)?;
+        }
+
+        let started_at = Local::now();
+        let rep_timer = Instant::now();
+        let initial: Vec<College> = colleges.iter().cloned()
+            .map(|mut c| { c.seed = rep_conf.random_seed; c })
+            .collect();
+        let sim = Simulation::new(rep_conf.clone(), initial);
+        let (_, history) = crate::simulation::run_epochs(sim, timer)?;
+        if conf.logging {
+            output_run_info(&rep_conf, &started_at, &rep_timer)?;
+        }
+
+        for c in history.iter().flatten() {
+            let entry = samples.entry((c.epoch, c.cid)).or_insert_with(|| Samples{
+                name: c.name.clone(),
+                institute: c.institute,
+                values: Default::default(),
+            });
+            for (i, v) in metric_values(c).iter().enumerate() {
+                entry.values[i].push(*v);
+            }
+        }
+    }
+
+    let path = format!("{}/replicates.csv", conf.output_dir);
+    let mut wtr = csv::Writer::from_path(&path)?;
+    for ((epoch, cid), s) in samples.iter() {
+        for (i, metric) in METRICS.iter().enumerate() {
+            wtr.serialize(summarize(*epoch, *cid, s, metric, &s.values[i]))?;
+        }
+    }
+    wtr.flush()?;
+    eprintln!("    反復集計結果 = {:?}", path);
+
+    Ok(())
+}
+
+// METRICSの順に指標値を取り出す
+fn metric_values(c: &CollegeResult) -> [f64; 4]{
+    [
+        c.admissons as f64,
+        c.new_deviation,
+        c.admissons as f64 / c.enroll as f64, //定員充足率
+        c.apply_count as f64,
+    ]
+}
+
+// 1指標分の統計量を計算。NaN（入学者0の偏差値など）は除外する。
+fn summarize(epoch: i32, cid: Cid, s: &Samples, metric: &str, values: &[f64]) -> ReplicateSummary{
+    let mut v: Vec<f64> = values.iter().cloned().filter(|x| x.is_finite()).collect();
+    v.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = v.len();
+    let mean = if n > 0 { v.iter().sum::<f64>() / n as f64 } else { f64::NAN };
+    let std = if n > 1 {
+        (v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+    } else {
+        0.0
+    };
+    ReplicateSummary{
+        epoch,
+        cid,
+        name: s.name.clone(),
+        institute: s.institute,
+        metric: metric.to_string(),
+        n,
+        mean,
+        std,
+        min: quantile(&v, 0.0),
+        q05: quantile(&v, 0.05),
+        q25: quantile(&v, 0.25),
+        q50: quantile(&v, 0.5),
+        q75: quantile(&v, 0.75),
+        q95: quantile(&v, 0.95),
+        max: quantile(&v, 1.0),
+    }
+}
+
+// ソート済み配列の分位点（線形補間）
+fn quantile(sorted: &[f64], p: f64) -> f64{
+    match sorted.len() {
+        0 => f64::NAN,
+        1 => sorted[0],
+        n => {
+            let pos = p * (n - 1) as f64;
+            let lower = pos.floor() as usize;
+            let upper = pos.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+        }
+    }
+}
@@ -0,0 +1,638 @@
+use rayon::prelude::*;
+use sprs::TriMat;
+use std::path::Path;
+use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::cmp::min;
+use rand::seq::index::sample;
+use superslice::Ext;
+use anyhow::Result;
+use chrono::Local;
+
+use crate::college::{College, Cid, CollegeResult};
+use crate::student::{self, Ronin, Sid, Student, StudentResult};
+use crate::config::Config;
+use crate::output::{output_history, output_result, output_run_info};
+use crate::replicate;
+use crate::checkpoint::Checkpoint;
+use crate::{Matrix, SidStatus};
+
+// 1エポックの実行結果：次step用大学オブジェクト、大学別集計結果、受験生別集計結果、状態遷移マトリクス（C x S）、
+// 次エポックに再受験する浪人生
+pub type StepResult = (Vec<College>, Vec<CollegeResult>, Vec<StudentResult>, Matrix, Vec<Ronin>);
+
+// シミュレーション本体。設定と大学エージェントを保持し、1エポックずつ進める。
+pub struct Simulation{
+    conf: Config,
+    colleges: Vec<College>, //次エポック用の大学エージェント
+    ronin: Vec<Ronin>, //2026.10.18 次エポックに再受験する浪人生
+    epoch: i32, //次に実行するエポック
+    status: Option<Matrix>, //直近エポックの状態遷移マトリクス　行=大学、列=受験生
+    college_results: Vec<CollegeResult>, //直近エポックの大学集計結果
+    student_results: Vec<StudentResult>, //直近エポックの受験生集計結果。ログ出力時のみ
+}
+
+impl Simulation {
+    pub fn new(conf: Config, colleges: Vec<College>) -> Self{
+        Self{
+            conf,
+            colleges,
+            ronin: Vec::new(),
+            epoch: 0,
+            status: None,
+            college_results: Vec::new(),
+            student_results: Vec::new(),
+        }
+    }
+
+    // 設定ファイルの大学初期値CSVから生成
+    pub fn from_conf(conf: Config) -> Result<Self>{
+        let colleges = College::from_conf(&conf)?;
+        Ok(Self::new(conf, colleges))
+    }
+
+    // 2026.10.18 チェックポイントから再開
+    pub fn from_checkpoint(conf: Config, cp: Checkpoint) -> Result<Self>{
+        cp.check(&conf)?;
+        let mut sim = Self::new(conf, cp.colleges);
+        sim.epoch = cp.epoch;
+        sim.ronin = cp.ronin;
+        Ok(sim)
+    }
+
+    // 次エポックから再開するためのチェックポイント
+    pub fn checkpoint(&self) -> Checkpoint{
+        Checkpoint::new(&self.conf, self.epoch, &self.colleges, &self.ronin)
+    }
+
+    // 1エポック分実行する。エラー時も大学エージェントを変えずにエポックは進める。
+    pub fn step(&mut self) -> Result<()>{
+        let epoch = self.epoch;
+        self.epoch += 1;
+        let (new_colls, college_results, student_results, status, ronin) =
+            step(epoch, &mut self.colleges, &self.ronin, &self.conf)?;
+        self.colleges = new_colls;
+        self.ronin = ronin;
+        self.college_results = college_results;
+        self.student_results = student_results;
+        self.status = Some(status);
+        Ok(())
+    }
+
+    // 全エポック実行済みか
+    pub fn is_finished(&self) -> bool{
+        self.epoch >= self.conf.epochs
+    }
+
+    pub fn conf(&self) -> &Config{
+        &self.conf
+    }
+
+    // 次に実行するエポック
+    pub fn epoch(&self) -> i32{
+        self.epoch
+    }
+
+    pub fn colleges(&self) -> &[College]{
+        &self.colleges
+    }
+
+    // 次エポックに再受験する浪人生
+    pub fn ronin(&self) -> &[Ronin]{
+        &self.ronin
+    }
+
+    pub fn into_colleges(self) -> Vec<College>{
+        self.colleges
+    }
+
+    // 直近エポックの状態遷移マトリクス。値はConfig::R_*を参照
+    pub fn status(&self) -> Option<&Matrix>{
+        self.status.as_ref()
+    }
+
+    pub fn college_results(&self) -> &[CollegeResult]{
+        &self.college_results
+    }
+
+    pub fn student_results(&self) -> &[StudentResult]{
+        &self.student_results
+    }
+}
+
+//main loop
+pub fn run(conf: &Config, timer: &Instant) -> Result<()>{
+    let started_at = Local::now();
+    let run_timer = Instant::now();
+
+    // 2026.10.18 複数シードを反復実行して統計量を集計
+    if conf.replicates > 1 {
+        //大学エージェント初期値
+        let colleges: Vec<College> = College::from_conf(conf)?;
+        replicate::run(conf, &colleges, timer)?;
+    } else {
+        // 2026.10.18 --resume指定時はチェックポイントの大学エージェントとエポックから再開
+        let sim = if conf.resume.is_empty() {
+            Simulation::from_conf(conf.clone())?
+        } else {
+            let cp = Checkpoint::load(Path::new(&conf.resume))?;
+            eprintln!("    チェックポイント {:?} epoch[{:02}]から再開", conf.resume, cp.epoch);
+            Simulation::from_checkpoint(conf.clone(), cp)?
+        };
+        let (colleges, _) = run_epochs(sim, timer)?;
+        output_history(conf, &colleges)?;
+    }
+
+    // 2026.10.18 実行記録を保存
+    if conf.has_output_dir() {
+        output_run_info(conf, &started_at, &run_timer)?;
+    }
+
+    Ok(())
+}
+
+// 1シード分の全エポックを実行し、最終の大学エージェントとエポック別の大学集計結果を返す
+// 2026.10.18 チェックポイント指定時はエポック毎に保存する
+pub fn run_epochs(mut sim: Simulation, timer: &Instant)
+    -> Result<(Vec<College>, Vec<Vec<CollegeResult>>)>{
+    let mut history: Vec<Vec<CollegeResult>> = Vec::new();
+    let conf = sim.conf().clone();
+    let conf = &conf;
+
+    while !sim.is_finished(){
+        let epoch = sim.epoch();
+        eprintln!("    epoch[{:02}]:start \t{:?}",epoch, timer.elapsed());
+        match sim.step(){
+            Ok(()) =>{
+                if conf.logging{
+                    output_result(conf, epoch, sim.college_results(), sim.student_results())?;
+                }
+                history.push(sim.college_results().to_vec());
+                if conf.ronin_rate > 0.0 {
+                    eprintln!("    epoch[{:02}]:ronin \t{}",epoch, sim.ronin().len());
+                }
+            },
+            Err(e) => eprintln!("step error epoch=[{:02}] msg=[{:?}]",epoch, e),
+        }
+        if conf.checkpoint{
+            sim.checkpoint().save(&conf.output_dir)?;
+        }
+    }
+
+    Ok((sim.into_colleges(), history))
+}
+
+// シミュレーション1回分実行
+// 2026.10.18 再現性：同じ設定・シードなら、スレッド数（--threads、RAYON_NUM_THREADS）によらず
+// college{NN}.csv、student{NN}.csv、履歴はバイト単位で同一になる。
+//   ・乱数は受験生毎の生成器のみを使い、受験生の処理順に依存しない
+//   ・並列集計（fold_with/reduce）は元の並び順を保ち、マトリクスはto_csrで並べ直す
+//   ・HashMap/HashSetの反復順に結果を依存させない
+// 確認は check_determinism.sh で行う。
+pub fn step(epoch: i32, colleges: &mut Vec<College>, ronin: &[Ronin], conf: &Config) -> Result<StepResult>{
+    
+    //Step:0 受験生エージェントを作成 2026.10.18 前エポックの浪人生を含む
+    let (mut students, dev_params) = Student::from_conf(conf, epoch as usize, ronin)?;
+
+    //国公立と私立大学に分けたベクターを用意
+    let (nationals, privates) = divide_colleges(colleges);
+    // 2026.10.18 国公立の日程別に募集のある大学
+    let national_rounds: Vec<Vec<College>> = (0..conf.national_round_rates.len())
+        .map(|round| nationals.iter().filter(|c| c.round_quota(conf, round) > 0).cloned().collect())
+        .collect();
+ 
+    //Step:0.5 推薦・総合型選抜（大学行動）。入学が決まった受験生は一般選抜に出願しない
+    let early_matrix = early(conf, epoch, &mut students, colleges);
+
+    //Step:1 出願 & 試験（学生行動）
+    let apply_matrix = apply(conf, dev_params, &mut students, &national_rounds, &privates, colleges.len());
+   
+    //Step:2 私立一次合格発表（大学行動）
+    let enroll1_matrix = enroll1(conf, colleges, &students, &apply_matrix);
+
+    //Step:3 入学判定１回目（学生行動）
+    let adm1_matrix  = admission1(conf, &mut students, colleges, &enroll1_matrix);
+
+    //Step:4 国公立合格発表（大学行動）
+    let (enroll2_matrix, withdrawn) = enroll2(conf, colleges, &students, &apply_matrix);
+    // 2026.10.18 前の日程の国公立に合格した受験生は後の日程の出願を取り下げる（受験者・不合格に数えない）
+    let apply_matrix = withdraw(&apply_matrix, &withdrawn);
+
+    //状態遷移マトリクス集計 => S x C 
+    let status = &enroll1_matrix + &enroll2_matrix;
+    let status = &status + &(apply_matrix.transpose_into());
+    let status = &status + &(adm1_matrix.transpose_into());
+    let status = &status + &(early_matrix.transpose_into());
+
+    //Step:5 国公立入学または保留中私立合格大学への入学（学生行動）
+    let adm2_matrix  = admission2(conf, &mut students, colleges, &status);
+
+    //状態遷移マトリクス集計 => C x S
+    let status = &adm2_matrix + &(status.transpose_into());
+    
+    //Step:6 私立追加合格発表（大学行動）
+    let enroll3_matrix = enroll3(conf, colleges, &students, &status);
+
+    //状態遷移マトリクス集計 => S x C
+    let status = &enroll3_matrix  + &(status.transpose_into());
+
+    //Step:7 入学先最終決定。追加合格大学への入学（学生行動）
+    let adm3_matrix  = admission3(conf, &mut students, colleges, &status);
+
+    //状態遷移マトリクス集計 => C x S
+    let status = &adm3_matrix + &(status.transpose_into());
+    
+    //シミュレーション結果を集計し、次step用大学オブジェクトと集計結果を生成
+    let (new_colleges, college_results, student_results) = settle(conf, epoch, &students, colleges, &status)?;
+
+    //Step:8 入学先のない受験生から次エポックの浪人生を決める（学生行動）
+    let new_ronin = student::carry_over(conf, &mut students);
+    Ok((new_colleges, college_results, student_results, status, new_ronin))
+}
+
+// 大学選択　＆　受験
+pub fn apply(conf: &Config, dev_params: (f64, f64), students: &mut Vec<Student>, national_rounds: &[Vec<College>], privates: &[College],
+    college_num: usize) -> Matrix{
+    let apply_list: Vec<(usize, usize)> = students.par_iter_mut()
+        .filter(|x| x.admission.is_none()) // 2026.10.18 推薦・総合型選抜で入学決定済みは除く
+        .fold_with( Vec::new(),
+            |mut acc, x|{
+                let idx = x.id;
+                let (_, entries) = x.apply(conf, dev_params, national_rounds, privates);
+                for college_idx in entries { acc.push((college_idx, idx));};
+                acc
+        })
+        .reduce(Vec::new, append_vector);
+
+    // 出願sparseマトリクス　行=大学、列=受験生、値1(出願) を作成
+    make_matrix(&apply_list, college_num, students.len(), Config::APPLY)
+}
+
+// 2026.10.18 推薦・総合型選抜　私立のみ。
+// 偏差値の高い大学から順に、大学偏差値 + early_band の範囲にいる未決定の受験生から
+// 入学定員 x early_rate 人を無作為に選び、入学を決定する。
+// 乱数は受験生とは別系列（random_seedを変えたエポック系列）を使う。
+const EARLY_SEED_SALT: u64 = 0x4541_524c_5900; // "EARLY"
+
+pub fn early(conf: &Config, epoch: i32, students: &mut [Student], colleges: &mut [College]) -> Matrix{
+    let mut early_list: Vec<(Cid, Sid)> = Vec::new();
+    for c in colleges.iter_mut() {
+        c.early_num = 0;
+    }
+    if conf.early_rate > 0.0 {
+        let mut rng = student::epoch_rng(conf.random_seed ^ EARLY_SEED_SALT, epoch as usize);
+        for c in colleges.iter_mut().rev().filter(|x| x.institute == Config::PRIVATE) {
+            let quota = (c.enroll as f64 * conf.early_rate).round() as usize;
+            // 受験生は偏差値昇順に並んでいる
+            let lower = students.lower_bound_by_key(&(c.score + conf.early_band[0] * 1000), |x| x.score);
+            let upper = students.upper_bound_by_key(&(c.score + conf.early_band[1] * 1000), |x| x.score);
+            let candidates: Vec<Sid> = (lower..upper)
+                .filter(|i| students[*i].admission.is_none())
+                .collect();
+            let picks = sample(&mut rng, candidates.len(), min(quota, candidates.len()));
+            for i in picks.iter() {
+                let sid = candidates[i];
+                students[sid].admission = Some(c.index);
+                early_list.push((c.index, sid));
+            }
+            c.early_num = picks.len();
+        }
+    }
+    // 入学者sparseマトリクス　行=大学、列=受験生、値9(推薦・総合型で入学) を作成
+    make_matrix(&early_list, colleges.len(), students.len(), Config::R_ADMISSION_EARLY)
+}
+
+// 合格者決定1　私立のみ
+pub fn enroll1(conf: &Config, colleges:&mut Vec<College>, students: &[Student], apply_mat: &Matrix) -> Matrix{
+    let enroll_list: Vec<(usize, usize)> = colleges.par_iter_mut()
+        .filter(|x| x.institute == Config::PRIVATE)
+        .fold_with(Vec::new(),
+            |mut acc, x|{
+                let idx = x.index;
+                let entries = x.enroll1(conf,students, apply_mat.outer_view(idx).unwrap().indices());
+                for student_idx in entries {acc.push((student_idx, idx));}
+                acc
+        })
+        .reduce( Vec::new, append_vector);
+
+    // 合格sparseマトリクス　行=受験生、列=大学、値2(合格) を作成
+    make_matrix(&enroll_list, students.len(), colleges.len(), Config::ENROLL_1ST)
+}
+
+//  入学決定1回名。私立大学のみ。志望校合格時に入学 or 入学金納付のみ or パス
+pub fn admission1
+    (conf: &Config, students: &mut Vec<Student>, colleges: &[College], enroll_mat: &Matrix) -> Matrix { 
+    let new_list: Vec<(Cid, SidStatus)> = students.par_iter_mut()
+        .fold_with( Vec::new(),
+            |mut acc, x|{
+                let idx = x.id;
+                let mut entries = x.admission1(conf, colleges, enroll_mat.outer_view(idx).unwrap().indices());
+                acc.append(&mut entries);
+                acc
+        })
+        .reduce( Vec::new, append_vector);
+
+    // 入学金納付者sparseマトリクス　行=大学、列=受験生、値４(入学金納付のみ) or 8（入学）を作成
+    make_matrix_any_value(&new_list, colleges.len(), students.len())
+}
+
+// 合格者決定２　国公立合格発表。合格者は入学も決定する。
+// 2026.10.18 日程（前期・中期・後期）順に実行。前の日程の合格者は後の日程の選抜から除く
+// 2026.10.18 日程順に合格者を決める。前の日程の合格者は後の日程では選考せず、取り下げた出願（大学, 受験生）として返す
+pub fn enroll2(conf: &Config, colleges:&mut Vec<College>, students: &[Student], mat: &Matrix) -> (Matrix, Vec<(usize, usize)>){
+    let mut passed: Vec<bool> = vec![false; students.len()];
+    let mut new_list: Vec<(usize, usize)> = Vec::new();
+    let mut withdrawn: Vec<(usize, usize)> = Vec::new();
+    for round in 0..conf.national_round_rates.len() {
+        withdrawn.extend(colleges.iter()
+            .filter(|x| x.institute != Config::PRIVATE)
+            .flat_map(|x| mat.outer_view(x.index).unwrap().indices().iter()
+                .filter(|sid| passed[**sid] && students[**sid].applied_national(round, x.index))
+                .map(|sid| (x.index, *sid))
+                .collect::<Vec<(usize, usize)>>()));
+        let round_list: Vec<(usize, usize)> = colleges.par_iter_mut()
+            .filter(|x| x.institute != Config::PRIVATE) //国公立のみ   
+            .fold_with(Vec::new(),
+                |mut acc, x|{
+                    let idx = x.index;
+                    let quota = x.national_quota(conf, round, mat.outer_view(idx).unwrap().nnz());
+                    let candidates: Vec<Sid> = mat.outer_view(idx).unwrap().indices().iter()
+                        .filter(|sid| !passed[**sid] && students[**sid].applied_national(round, idx))
+                        .cloned()
+                        .collect();
+                    let entries = x.enroll2(quota, students, &candidates);
+                    for student_idx in entries {acc.push((student_idx, idx));}
+                    acc
+            })
+            .reduce( Vec::new, append_vector);
+        for (sid, _) in round_list.iter() {
+            passed[*sid] = true;
+        }
+        new_list.extend(round_list);
+    }
+
+
+    // 合格sparseマトリクス　行=受験生、列=大学、値2(合格) を作成
+    (make_matrix(&new_list, students.len(), colleges.len(), Config::ENROLL_2ND), withdrawn)
+}
+
+// 2026.10.18 出願マトリクス（行=大学、列=受験生）から取り下げた出願を除く
+pub fn withdraw(apply_mat: &Matrix, withdrawn: &[(usize, usize)]) -> Matrix{
+    if withdrawn.is_empty() {
+        return apply_mat.clone()
+    }
+    let withdrawn: HashSet<(usize, usize)> = withdrawn.iter().cloned().collect();
+    let list: Vec<(usize, usize)> = apply_mat.iter()
+        .map(|(_, (cidx, sid))| (cidx, sid))
+        .filter(|x| !withdrawn.contains(x))
+        .collect();
+    make_matrix(&list, apply_mat.rows(), apply_mat.cols(), Config::APPLY)
+}
+
+// 合格者決定３　私立追加合格発表。
+pub fn enroll3(conf: &Config, colleges:&mut Vec<College>, students: &[Student], mat: &Matrix) -> Matrix{
+    let new_list: Vec<(usize, usize)> = colleges.par_iter_mut()
+        .filter(|x| x.institute == Config::PRIVATE) //私立のみ   
+        .fold_with(Vec::new(),
+            |mut acc, x|{
+                let idx = x.index;
+                let entries = x.enroll3(conf, students, mat, idx);
+                for student_idx in entries {acc.push((student_idx, idx));}
+                acc
+        })
+        .reduce(Vec::new, append_vector);
+
+    // 合格sparseマトリクス　行=受験生、列=大学、値32(追加合格) を作成
+    make_matrix(&new_list, students.len(), colleges.len(), Config::ENROLL_3RD)
+}
+
+
+// 国公立大学への入学．国公立不合格で保留中私立があればそこに入学
+pub fn admission2
+    (conf: &Config, students: &mut Vec<Student>, colleges: &[College], mat: &Matrix) -> Matrix { 
+    let admission_list: Vec<(Cid, Sid)> = students.par_iter_mut()
+        .filter(|x| x.admission.is_none())
+        .fold_with( Vec::new(),
+            |mut acc, x|{
+                let idx = x.id;
+                if let Some(college_idx) = x.admission2(conf, colleges, mat, idx){
+                    acc.push((college_idx, idx));
+                }
+                acc
+        })
+        .reduce( Vec::new, append_vector);
+
+    // 入学者sparseマトリクス　行=大学、列=受験生、値32(保留中私立入学) を作成
+    make_matrix(&admission_list, colleges.len(), students.len(), Config::ADMISSION_2ND)
+}
+
+// 追加合格私立大学への入学
+pub fn admission3
+    (conf: &Config, students: &mut Vec<Student>, colleges: &[College], mat: &Matrix) -> Matrix { 
+    let admission_list: Vec<(Cid, Sid)> = students.par_iter_mut()
+        .filter(|x| x.admission.is_none())
+        .fold_with( Vec::new(),
+            |mut acc, x|{
+                let idx = x.id;
+                if let Some(college_idx) = x.admission3(conf, colleges, mat, idx){
+                    acc.push((college_idx, idx));
+                }
+                acc
+        })
+        .reduce( Vec::new, append_vector);
+
+    // 入学者sparseマトリクス　行=大学、列=受験生、値64(最終決定入学先) を作成
+    make_matrix(&admission_list, colleges.len(), students.len(), Config::ADMISSION_3RD)
+}
+
+fn append_vector<T>(mut left: Vec<T>, mut right: Vec<T>) -> Vec<T>{
+    left.append(&mut right);
+    left
+}
+
+// sparseマトリクス作成　行=受験生or大学、列=大学or受験生、値 を作成
+// 2026.10.18 to_csrで行・列順に並べ、重複は加算するので結果はlistの並び順に依存しない
+pub fn make_matrix(list: &[(usize, usize)], rows: usize, cols: usize, value: u8) -> Matrix{
+    let mut trimat = TriMat::new((rows, cols));
+    list.iter().for_each(|(row, col)| trimat.add_triplet(*row, *col, value));
+    trimat.to_csr()
+}
+
+// sparseマトリクス作成　行=受験生or大学、列=大学or受験生、値はlistから取得
+pub fn make_matrix_any_value(list: &[(usize, SidStatus)], rows: usize, cols: usize) -> Matrix{
+    let mut trimat = TriMat::new((rows, cols));
+    list.iter().for_each(|(row, col)| trimat.add_triplet(*row, col.0, col.1));
+    trimat.to_csr()
+}
+
+
+//シミュレーション結果を集計し、次step用大学オブジェクトと集計結果を生成
+pub fn settle(conf: &Config, epoch: i32, students: &[Student], colleges: &[College], status: &Matrix)
+    ->Result<(Vec<College>, Vec<CollegeResult>, Vec<StudentResult>)>{
+
+    let mut new_colleges: Vec<College> = Vec::new();
+    let mut college_results: Vec<CollegeResult> = Vec::new();
+    let mut student_results: Vec<StudentResult> = Vec::new();
+    let mut student_map = HashMap::new();
+
+    for x in colleges {
+        let mut new_dev: f64 = 0.0; //入学者の偏差値合計
+        let mut counters = HashMap::new();
+        let values = status.outer_view(x.index).unwrap().indices().iter()
+            .map(|col|{
+                if let Some(val) = status.get(x.index, *col){
+                    //状態値別に件数を集計
+                    let counter = counters.entry(*val).or_insert(0);
+                    *counter += 1;
+
+                    match *val{ //合格者の試験時偏差値を集計 => 2021.12.12 本来の偏差値に変更
+                        Config::R_ADMISSION_1ST | Config::R_ADMISSION_2ND |
+                        Config::R_ADMISSION_3RD | Config::R_ADMISSION_RSV |
+                        Config::R_ADMISSION_EARLY => {
+                            // new_dev += *students[*col].exam_dev(x.index) as f64 / 1000.0;
+                            new_dev += students[*col].score as f64 / 1000.0;
+                        },
+                        _ => (),
+                    }
+                    Some( (*col,*val) )
+                }else{
+                    None
+                }
+            }).collect::<Vec<Option<(Sid, u8)>>>();
+
+        //
+        // //学生別ログ出力用ハッシュマップ作成。key=Sid, value=Vec<(Cid,status)>
+        if conf.logging {
+            values.iter().for_each(|v|{
+                if let Some((sid, val)) = v{
+                    let c_vec = student_map.entry(*sid).or_insert(Vec::new());
+                    c_vec.push((x.index, *val));
+
+                }
+            });
+        }
+        //件数集計
+        //一次合格者数
+        let enroll_1st_count = count(&values, Config::ENROLL_1ST) + 
+                               count(&values, Config::ENROLL_2ND);
+        //追加合格者数
+        let enroll_add_count = count(&values, Config::ENROLL_3RD);
+
+        // 2026.10.18 推薦・総合型選抜入学者数
+        let admission_early_count = count_eq(&counters, &Config::R_ADMISSION_EARLY);
+
+        //一次合格入学者数
+        let admission_1st_count = count_eq(&counters, &Config::R_ADMISSION_1ST) +
+                                  count_eq(&counters, &Config::R_ADMISSION_2ND);
+
+         //一次合格保留後入学者数
+        let admission_rsv_count = count_eq(&counters, &Config::R_ADMISSION_RSV);
+
+        //追加合格入学者数
+        let admission_add_count = count_eq(&counters, &Config::R_ADMISSION_3RD);
+        
+        //入学金納付のみ者数
+        let paid_only_count = count_eq(&counters, &Config::R_DECLINE1_PAID);
+
+        //入学者総数
+        // 2021.12.29 
+        // let admissons_all = admission_1st_count + admission_rsv_count + admission_add_count;
+        let admissons_all = count_admissons(&values);
+
+        //大学集計結果オブジェクト作成
+        let college_result = CollegeResult{
+            epoch,
+            index: x.index, //偏差値昇順ソート後の連番。配列のインデックス
+            cid: x.cid, //旺文社の大学番号
+            name: x.name.clone(),  //  大学名
+            institute: x.institute, // 設置区分：1国立 2公立 3私立
+            pref: x.pref,
+            urban: x.urban.clone(),
+            own_scale: x.college_scale(),
+            dev: x.dev, // 偏差値
+            enroll: x.enroll, //　入学定員数
+            over_rate: x.over_rate, //合格者超過率
+        
+            //シミュレーション結果
+            apply_count: values.len() as i32, //受験者数
+            decline1: count_eq(&counters, &Config::R_DECLINE1), //辞退
+            decline2: count_eq(&counters, &Config::R_DECLINE2), //追加合格辞退
+            enroll_1st_count,//正規合格数
+            enroll_add_count, //追加合格数
+            paid_only_count, //入学金納付のみ
+
+            admisson_early: admission_early_count, //推薦・総合型選抜で入学
+            admisson_1st: admission_1st_count, //一次、国立合格で入学
+            admisson_rsv: admission_rsv_count, //一次保留後入学
+            admisson_add: admission_add_count, //追加合格入学
+
+            admissons: admissons_all, //最終入学者数
+            new_deviation: new_dev / admissons_all as f64, //入学者偏差値平均
+            payments: admissons_all + paid_only_count, //入学金納付者数
+
+            entrance_fee: x.entrance_fee,
+            tuition: x.tuition,
+            fee_revenue: x.entrance_fee * (admissons_all + paid_only_count) as u64,
+            forfeited_fees: x.entrance_fee * paid_only_count as u64,
+            tuition_revenue: x.tuition * admissons_all as u64,
+        };
+
+        //次エポック用大学エージェント作成
+        new_colleges.push(x.update(conf, &college_result));
+        //大学入試結果 2026.10.18 反復集計で使うのでログ出力有無によらず保持
+        college_results.push(college_result);
+    };
+
+    //大学を偏差値順にソート
+    new_colleges.par_sort_by(|a, b| a.score.cmp(&b.score));
+    //index振り直し
+    for (i, c) in new_colleges.iter_mut().enumerate() {c.index = i}
+
+    //受験生入試結果生成
+    if conf.logging {
+        student_results = student::settle(epoch, students, &mut student_map, colleges);
+    }
+    
+    Ok((new_colleges, college_results, student_results))
+}
+
+
+//国公立と私立を分ける
+pub fn divide_colleges(colleges: &[College]) -> (Vec<College>, Vec<College>){
+    let privates: Vec<College> = colleges.iter()
+        .filter(|x| x.institute == Config::PRIVATE)
+        .cloned()
+        .collect();
+    let nationals: Vec<College> = colleges.iter()
+        .filter(|x| x.institute != Config::PRIVATE)
+        .cloned()
+        .collect();
+    (nationals, privates)
+}
+
+//ハッシュからデータを取得。キーがない時には0を返す。
+fn count_eq(m: &HashMap<u8, i32>, key: &u8) -> i32{
+    if let Some(val) = m.get(key){
+        *val
+    }else{
+        0
+    }
+}
+
+//bitマップ＆で一致する個数を取得する
+fn count(values: &[Option<(Sid, u8)>], key: u8) -> i32{
+    values.iter().map(|x| x.unwrap().1)
+        .filter(|x| x & key != 0)
+        .count() as i32
+}
+
+//bitマップ＆で一致する個数を取得する 2021.12.29 合格者バグかくにんの
+fn count_admissons(values: &[Option<(Sid, u8)>]) -> i32{
+    values.iter().map(|x| x.unwrap().1)
+        .filter(|x|
+             x & Config::ADMISSION_1ST != 0 ||
+             x & Config::ENROLL_2ND != 0 ||
+             x & Config::ADMISSION_2ND != 0 ||
+             x & Config::ADMISSION_3RD != 0
+        ).count() as i32
+}
\ No newline at end of file
@@ -0,0 +1,157 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde::de::DeserializeOwned;
+use bincode::Options;
+
+// 2026.10.18 シミュレーション結果の出力形式
+//   csv   従来のCSV（既定）
+//   jsonl 1行1レコードのJSON
+//   bin   bincodeによるバイナリ。1レコードずつ行単位で連続して書く（列指向ではない）。
+//         60万人規模の学生結果を高速に書き込み・読み込みするため
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Jsonl,
+    Bin,
+}
+
+impl OutputFormat {
+    // 出力ファイルの拡張子
+    pub fn extension(&self) -> &'static str{
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Bin => "bin",
+        }
+    }
+
+    // 拡張子から出力形式を判定
+    pub fn from_path(path: &Path) -> Result<Self>{
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Ok(OutputFormat::Csv),
+            Some("jsonl") => Ok(OutputFormat::Jsonl),
+            Some("bin") => Ok(OutputFormat::Bin),
+            _ => Err(anyhow!("unknown result file format: {:?}", path)),
+        }
+    }
+}
+
+// 2026.10.18 結果レコードの実数項目の読み込み。JSONでは非有限値（NaN、無限大）がnullで書き出されるので、
+// nullはNaNとして読む。バイナリは値をそのまま読む
+//   #[serde(deserialize_with = "crate::sink::f64_or_nan")]
+pub fn f64_or_nan<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error>{
+    if deserializer.is_human_readable() {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+    } else {
+        f64::deserialize(deserializer)
+    }
+}
+
+// 結果レコードの書き出し先
+pub trait ResultSink<T> {
+    fn write(&mut self, record: &T) -> Result<()>;
+    fn finish(&mut self) -> Result<()>;
+
+    fn write_all(&mut self, records: &[T]) -> Result<()>{
+        for r in records {
+            self.write(r)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct CsvSink{
+    wtr: csv::Writer<File>,
+}
+
+impl<T: Serialize> ResultSink<T> for CsvSink {
+    fn write(&mut self, record: &T) -> Result<()>{
+        self.wtr.serialize(record)?;
+        Ok(())
+    }
+    fn finish(&mut self) -> Result<()>{
+        self.wtr.flush()?;
+        Ok(())
+    }
+}
+
+pub struct JsonlSink{
+    wtr: BufWriter<File>,
+}
+
+impl<T: Serialize> ResultSink<T> for JsonlSink {
+    fn write(&mut self, record: &T) -> Result<()>{
+        serde_json::to_writer(&mut self.wtr, record)?;
+        self.wtr.write_all(b"\n")?;
+        Ok(())
+    }
+    fn finish(&mut self) -> Result<()>{
+        self.wtr.flush()?;
+        Ok(())
+    }
+}
+
+// bincodeのレコードを連続して書き込む。件数は持たずファイル末尾まで読む
+// 整数は可変長エンコード（DefaultOptions）でCSVより小さくする
+pub struct BinSink{
+    wtr: BufWriter<File>,
+}
+
+impl<T: Serialize> ResultSink<T> for BinSink {
+    fn write(&mut self, record: &T) -> Result<()>{
+        bincode::DefaultOptions::new().serialize_into(&mut self.wtr, record)?;
+        Ok(())
+    }
+    fn finish(&mut self) -> Result<()>{
+        self.wtr.flush()?;
+        Ok(())
+    }
+}
+
+// 出力形式に応じた書き出し先を作成。pathの拡張子は形式に合わせて付け替える
+pub fn create_sink<T: Serialize>(format: OutputFormat, path: &Path) -> Result<Box<dyn ResultSink<T>>>{
+    let path = path.with_extension(format.extension());
+    let file = File::create(&path).with_context(|| format!("cannot create {:?}", path))?;
+    let sink: Box<dyn ResultSink<T>> = match format {
+        OutputFormat::Csv => Box::new(CsvSink{ wtr: csv::Writer::from_writer(file) }),
+        OutputFormat::Jsonl => Box::new(JsonlSink{ wtr: BufWriter::new(file) }),
+        OutputFormat::Bin => Box::new(BinSink{ wtr: BufWriter::new(file) }),
+    };
+    Ok(sink)
+}
+
+// 結果ファイルを読み込む。形式は拡張子で判定
+//   例) read_results::<StudentResult>(Path::new("output/xxx/student01.bin"))
+pub fn read_results<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>>{
+    let file = File::open(path).with_context(|| format!("cannot open {:?}", path))?;
+    let mut records: Vec<T> = Vec::new();
+    match OutputFormat::from_path(path)? {
+        OutputFormat::Csv => {
+            let mut rdr = csv::Reader::from_reader(file);
+            for r in rdr.deserialize() {
+                records.push(r?);
+            }
+        },
+        OutputFormat::Jsonl => {
+            let stream = serde_json::Deserializer::from_reader(BufReader::new(file)).into_iter::<T>();
+            for r in stream {
+                records.push(r?);
+            }
+        },
+        OutputFormat::Bin => {
+            let mut rdr = BufReader::new(file);
+            // レコードの区切りで読み終えた場合のみ正常終了。途中で切れていればエラー
+            while !rdr.fill_buf()?.is_empty() {
+                let r = bincode::DefaultOptions::new().deserialize_from(&mut rdr)
+                    .with_context(|| format!("{:?}: record {} is truncated or broken", path, records.len()))?;
+                records.push(r);
+            }
+        },
+    }
+    Ok(records)
+}
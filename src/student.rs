@@ -1,17 +1,21 @@
 use crate::college::{College, Cid};
-use crate::config::Config;
+use crate::config::{Config, RngMode};
+use crate::population::{self, StudentRecord};
+use crate::geo;
 use crate::Matrix;
 
 use rayon::prelude::*;
 use rand::Rng;
 use rand::distributions::{Distribution,WeightedIndex};
 use rand_distr::Normal; //StandardNormal
+use anyhow::Result;
 use rand::seq::index::sample;
 use rand_xoshiro::rand_core::SeedableRng;
 use rand_xoshiro::Xoshiro256StarStar;
 use superslice::Ext;
 use std::collections::{HashSet, HashMap};
-use serde::Serialize;
+use std::cmp::Reverse;
+use serde::{Deserialize, Serialize};
 
 pub type Sid = usize; //学生ID
 
@@ -29,56 +33,116 @@ pub struct Student{
     pub c_map: HashMap<usize, i32>, //出願した大学のインデックスと、試験成績のマップ
     pub pattern: ApplyPattern, //併願パターン　
     pub admission: Option<Cid>, //入学を決めた大学のインデックス
+    pub pref: u8, //2026.10.18 都道府県番号：1-47。0は不明
+    pub nationals: Vec<(usize, Cid)>, //2026.10.18 国公立の出願先（日程, 大学インデックス）
+    pub ronin: u32, //2026.10.18 浪人年数。0は現役
+    pub budget: Option<u64>, //2026.10.18 入学金に充てられる予算（円）。Noneは制約なし
     pub rng: Xoshiro256StarStar, //乱数生成器
 }
 
 impl Student {
     pub fn new(fscore: f64, seed: u64) -> Self{
+        Self::with_rng(fscore, Xoshiro256StarStar::seed_from_u64(seed))
+    }
+
+    pub fn with_rng(fscore: f64, rng: Xoshiro256StarStar) -> Self{
         Self{id: 0,
             score: (fscore * 1000.0).round() as i32,
             c_map: HashMap::new(),
             pattern: ApplyPattern::Both,
             admission: None,
-            rng: Xoshiro256StarStar::seed_from_u64(seed)
+            pref: 0,
+            nationals: Vec::new(),
+            ronin: 0,
+            budget: None,
+            rng,
         }
     }
 
-    pub fn from_conf(conf: &Config, epoch: usize) -> Vec<Self> {
-        let mut rng1 = Xoshiro256StarStar::seed_from_u64(conf.random_seed);
-        let normal = Normal::new(conf.student_dev_mu, conf.student_dev_sigma).unwrap();
-
-        let mut students: Vec<Self> = normal.sample_iter(&mut rng1)
-            .take(conf.student_number[epoch]) //今回の年度の志願者数分生成
-            .collect::<Vec<f64>>()
-            .into_iter()
+    // 2026.10.18 今回のエポックの受験生の偏差値の平均と標準偏差（浪人生を除く）も返す
+    pub fn from_conf(conf: &Config, epoch: usize, ronin: &[Ronin]) -> Result<(Vec<Self>, (f64, f64))> {
+        // 2026.10.18 エポック別の乱数列：random_seedの主系列をエポック数+1回long_jumpした系列から
+        // 偏差値を生成し、受験生i番目にはその系列をi+1回jumpした系列を割り当てる。
+        // エポック間・受験生間で系列が重ならず、シードとエポックだけで再現できる。
+        // 従来方式（legacy）は毎エポック同じシードから生成する
+        let mut rng1 = match conf.rng_mode {
+            RngMode::Legacy => Xoshiro256StarStar::seed_from_u64(conf.random_seed),
+            RngMode::Epoch => epoch_rng(conf.random_seed, epoch),
+        };
+        let mut stream = rng1.clone();
+
+        // 2026.10.18 偏差値は設定に応じて正規分布・受験生CSV・ヒストグラムから作る
+        // 2026.10.18 前エポックの浪人生は新規の受験生の後ろに加える
+        let mut records = population::draw(conf, epoch, &mut rng1)?;
+        let dev_params = population::dev_params(conf, epoch, &records);
+        records.extend(ronin.iter().map(|r| r.to_record()));
+        let mut students: Vec<(Self, Option<u8>)> = records.into_iter()
             .enumerate()
-            .map(|(i, x)| Student::new(x, conf.random_seed + i as u64))
+            .map(|(i, r)| {
+                let rng = match conf.rng_mode {
+                    RngMode::Legacy => Xoshiro256StarStar::seed_from_u64(conf.random_seed + i as u64),
+                    RngMode::Epoch => {
+                        stream.jump();
+                        stream.clone()
+                    },
+                };
+                let mut x = Student::with_rng(r.score, rng);
+                x.pref = r.pref.unwrap_or(0);
+                x.ronin = r.ronin;
+                x.budget = r.budget;
+                (x, r.pattern)
+            })
             .collect();
-        students.par_sort_by(|a, b| a.score.cmp(&b.score));
-        students.into_par_iter()
+        students.par_sort_by(|a, b| a.0.score.cmp(&b.0.score));
+        // 2026.10.18 地理モデル有効時は出身都道府県の分布
+        let pref_dist = if conf.geo_model { Some(geo::pref_dist(conf)?) } else { None };
+        // 2026.10.18 資金制約有効時は予算の分布
+        let budget_dist = if conf.budget_model {
+            Some(Normal::new(conf.student_budget_mu, conf.student_budget_sigma)?)
+        } else {
+            None
+        };
+        let students = students.into_par_iter()
             .enumerate()
-            .map(|(i, mut x)| {
+            .map(|(i, (mut x, pattern))| {
                 //連番をIDとして設定。偏差値が低いほど若い。
                 x.id = i;
-                //併願パターンを決定
-                x.pattern = if x.rng.gen_bool(conf.national_prob){
-                    ApplyPattern::Both //国立も受験
-                } else {
-                    ApplyPattern::PrivateOnly
+                //併願パターンを決定 2026.10.18 受験生CSVで指定があればそれを使う
+                x.pattern = match pattern {
+                    Some(1) => ApplyPattern::Both,
+                    Some(_) => ApplyPattern::PrivateOnly,
+                    None => if x.rng.gen_bool(conf.national_prob){
+                        ApplyPattern::Both //国立も受験
+                    } else {
+                        ApplyPattern::PrivateOnly
+                    },
                 };
+                //出身都道府県を決定 2026.10.18 受験生CSVで指定があればそれを使う
+                if let Some(dist) = &pref_dist {
+                    if x.pref == 0 {
+                        x.pref = geo::draw_pref(dist, &mut x.rng);
+                    }
+                }
+                //予算を決定 2026.10.18 受験生CSVで指定があればそれを使う
+                if let Some(dist) = &budget_dist {
+                    if x.budget.is_none() {
+                        x.budget = Some(dist.sample(&mut x.rng).max(0.0).round() as u64);
+                    }
+                }
                 x
             })
-            .collect()
+            .collect();
+        Ok((students, dev_params))
     }
     
     // ランク別の大学グループを作成し、各グループから受験大学を選択して出願＆受験する。
     // ランクの範囲、数、各ランクから何校選ぶかはconfigの設定に従う。
     // 試験結果として誤差を加えた自分の偏差値を大学インデックスをキーとしたMapに保存する。
-    pub fn apply(&mut self, conf: &Config, nationals: &[College], privates: &[College]) -> (Vec<(usize, usize)>, Vec<Cid>){
+    // 2026.10.18 国公立は日程（前期・中期・後期）毎に、その日程の募集がある大学のリストを受け取る
+    // 2026.10.18 ダイヤモンド型の判定と試験の誤差は今回のエポックの受験生の偏差値の平均と標準偏差（dev_params）を使う
+    pub fn apply(&mut self, conf: &Config, dev_params: (f64, f64), national_rounds: &[Vec<College>], privates: &[College]) -> (Vec<(usize, usize)>, Vec<Cid>){
         
         let mut c_vec:Vec<Cid> ; //選択した大学
-        let bounds: Vec<(usize, usize)> ;//私立大学ランク範囲
-        let mut national: Option<Cid> = None;
 
         //出願数のパターンをランダムに選択
         let pattern: usize = if self.rng.gen_bool(conf.first_pattern_rate){
@@ -87,57 +151,63 @@ impl Student {
             1 //延べ7校
         };
 
-        match self.pattern {
-            ApplyPattern::Both => {
-                // 1:国公立を１校選択
-                national = self.from_nationals(conf, nationals);
-            },
-            _ => (),
+        if let ApplyPattern::Both = self.pattern {
+            // 1:国公立を日程毎に１校選択 2026.10.18 前の日程と同じ大学になった日程は出願しない
+            for (round, nationals) in national_rounds.iter().enumerate() {
+                if let Some(n) = self.from_nationals(conf, nationals) {
+                    if !self.nationals.iter().any(|(_, c)| *c == n) {
+                        self.nationals.push((round, n));
+                    }
+                }
+            }
         }
         // 2:私立大学から複数選択
         // 2021.12.31 偏差値平均以下の学生はダイヤモアンド型受験
-        let select_nums = if self.score as f64 / 1000.0  > conf.student_dev_mu{
+        let (dev_mu, dev_sigma) = dev_params;
+        let select_nums = if self.score as f64 / 1000.0  > dev_mu{
             conf.college_rank_select_number.to_vec()
         }else{
             conf.college_rank_select_number_diamond.to_vec()
         };
 
         let rank_num = conf.college_rank_lower.len();
-        bounds = (0..rank_num).into_iter()
-            .map(|i| self.get_bounds(conf.college_rank_lower[i], conf.college_rank_upper[i], privates))
+        //私立大学ランク範囲
+        let bounds: Vec<(usize, usize)> = (0..rank_num)
+            .map(|i| self.get_bounds(conf, conf.college_rank_lower[i], conf.college_rank_upper[i], privates))
             .collect();
         // println!("inner:bounds:{:?}",bounds);
         // ABC大学ランク毎に指定選択数だけ大学を選ぶ
-        c_vec = (0..rank_num).into_iter()
-            .map(|i| {
+        c_vec = (0..rank_num)
+            .flat_map(|i| {
                 //Aランク(i==0)時、国公立にも出願する場合には選択数をその分１つ減らす
                 let mut select_number = select_nums[pattern][i];
-                match national{
-                    None =>  (), //そのまま
-                    _ => if i == 0 { select_number -= 1 } //１校分減らす
+                if !self.nationals.is_empty() && i == 0 {
+                    select_number -= 1 //１校分減らす
                 }
-                self.select_college(privates, bounds[i], select_number)
+                self.select_college(conf, privates, bounds[i], select_number)
             })
-            .flatten()
-            .collect::<HashSet<Cid>>() //一旦Setにして重複を削除
-            .into_iter()
             .collect();
+        // 重複を削除 2026.10.18 選択順を保つ（HashSetの反復順は実行毎に変わるため）
+        let mut selected: HashSet<Cid> = HashSet::new();
+        c_vec.retain(|c| selected.insert(*c));
         
         // 3:国公立があれば配列に追加
-        if let Some(n) = national{
-            c_vec.push(n);
-        }
+        c_vec.extend(self.nationals.iter().map(|(_, c)| *c));
         // 4:試験　大学毎の試験成績を記録
         c_vec.iter().for_each(|c_idx| {
-            let exam_result = self.exam();
+            let exam_result = self.exam(dev_sigma);
             self.c_map.insert(*c_idx, exam_result);
         });
         (bounds, c_vec)
     }
 
     // 国公立大学から1校選択
+    #[allow(clippy::wrong_self_convention)]
     pub fn from_nationals(&mut self, conf: &Config, nationals: &[College]) -> Option<Cid>{
-        let bounds: (usize, usize) = self.get_bounds(conf.national_range[0], conf.national_range[1], nationals);
+        if nationals.is_empty() {
+            return None
+        }
+        let bounds: (usize, usize) = self.get_bounds(conf, conf.national_range[0], conf.national_range[1], nationals);
         // println!("inner:bounds:{:?}",bound);
         match bounds{
             // 偏差値に合う国公立なし
@@ -147,8 +217,7 @@ impl Student {
                 if size <= 1 {
                     Some(nationals[bounds.0].index)
                 } else {
-                    let idx_v = self.random_select(Config::get().college_select_by_enroll,
-                         size as usize, 1, bounds.0, nationals);
+                    let idx_v = self.random_select(conf, size as usize, 1, bounds.0, nationals);
                     Some(nationals[idx_v[0]].index)
                 }
             }
@@ -157,13 +226,13 @@ impl Student {
 
 
     // 大学ランク別グループの下限と上限（配列のインデックス）を返す。
-    fn get_bounds(&self, lower: i32, upper: i32, colleges: &[College]) -> (usize, usize){
+    fn get_bounds(&self, conf: &Config, lower: i32, upper: i32, colleges: &[College]) -> (usize, usize){
         //2021.12.31 学生偏差値上限と下限の緩和
-        let dev = if Config::get().college_dev_rift &&
-            self.score - 5000 < Config::get().college_dev_lower{
-            Config::get().college_dev_lower + 5000
-        }else if self.score + 5000 > Config::get().college_dev_upper{
-            Config::get().college_dev_upper - 5000
+        let dev = if conf.college_dev_rift &&
+            self.score - 5000 < conf.college_dev_lower{
+            conf.college_dev_lower + 5000
+        }else if self.score + 5000 > conf.college_dev_upper{
+            conf.college_dev_upper - 5000
         }else {
             self.score
         };
@@ -171,17 +240,15 @@ impl Student {
         let max_size = colleges.len();
         let mut lower = colleges.lower_bound_by_key(&(dev + lower * 1000),|x| x.score);
         let mut upper = colleges.upper_bound_by_key(&(dev + upper * 1000),|x| x.score);
-        if upper != 0{
-            upper = upper - 1;
-        } 
+        upper = upper.saturating_sub(1);
         if lower >= max_size{
-            lower = lower - 1;
+            lower -= 1;
         }
         (lower, upper)
     }
 
     // 私立大学ランク別グループから、configでグループ別に指定された数だけ出願校を選択する。
-    fn select_college(&mut self, colleges: &[College], bound: (usize, usize), select_number: usize) -> Vec<usize>{
+    fn select_college(&mut self, conf: &Config, colleges: &[College], bound: (usize, usize), select_number: usize) -> Vec<usize>{
         let mut v: Vec<usize> = Vec::new();
         let size = (bound.1 as i32) - (bound.0 as i32) + 1;
         //上限と下限が同値、1校しかなかった場合、
@@ -189,10 +256,10 @@ impl Student {
             v.push(bound.0);
         // 大学数が出願数以下だった場合。
         }else if size <= select_number as i32{
-            (bound.0..=bound.1).into_iter().for_each(|x| v.push(x));
+            (bound.0..=bound.1).for_each(|x| v.push(x));
         // 大学グループから入学定員に比例した確率または一様分布で出願数だけ大学を選択
         }else {
-            v = self.random_select(Config::get().college_select_by_enroll, size as usize, select_number, bound.0, colleges);
+            v = self.random_select(conf, size as usize, select_number, bound.0, colleges);
         }
         //私立大学配列上のインデクスから、その先の大学全体のインデックスに変換してから値を返す
         v.iter().map(|x| colleges[*x].index).collect()
@@ -200,18 +267,36 @@ impl Student {
 
     // 入学試験。自分の偏差値 + 標準正規分布誤差を返す。
     // 2021.12.01 誤差をN(0, 生成時標準偏差^2)に変更
-    fn exam(&mut self) -> i32{
+    fn exam(&mut self, sigma: f64) -> i32{
         // self.score + (self.rng.sample::<f32, _>(StandardNormal) * 1000.0).round() as i32
-        let normal = Normal::new(0.0, Config::get().student_dev_sigma).unwrap(); // 2021.12.11 正規分布生成器
+        let normal = Normal::new(0.0, sigma).unwrap(); // 2021.12.11 正規分布生成器
         self.score + (normal.sample(& mut self.rng) * 100.0).round() as i32 // 2021.12.24揺らぎを10%にする
     }
 
     //一様分布又は入学定員に比例した確率で大学を選択
     // 2021.11.29 入学定員ではなく志願者数に比例させる
-    fn random_select(&mut self, proportional: bool, size: usize, select_number: usize, offset: usize, colleges: &[College]) -> Vec<usize>{
-        if proportional{
+    // 2026.10.18 地理モデル有効時は出身都道府県からの距離・地元・都市圏の倍率を掛ける
+    fn random_select(&mut self, conf: &Config, size: usize, select_number: usize, offset: usize, colleges: &[College]) -> Vec<usize>{
+        if conf.geo_model{
+            let mut v: Vec<usize> = Vec::new();
+            let choice = (0..size).map(|x|x + offset).collect::<Vec<usize>>();
+            let weight = choice.iter()
+                .map(|x| {
+                    let base = if conf.college_select_by_enroll { colleges[*x].applicant_num as f64 } else { 1.0 };
+                    base * geo::select_factor(conf, self.pref, &colleges[*x])
+                })
+                .collect::<Vec<f64>>();
+            let dist = WeightedIndex::new(weight).unwrap();
+            while v.len() < select_number{
+                let bingo = choice[dist.sample(&mut self.rng)];
+                if !v.contains(&bingo){
+                    v.push(bingo);
+                }
+            }
+            v
+        } else if conf.college_select_by_enroll{
             let mut v: Vec<usize> = Vec::new();
-            let choice = (0..size).into_iter().map(|x|x + offset).collect::<Vec<usize>>();
+            let choice = (0..size).map(|x|x + offset).collect::<Vec<usize>>();
             let weight = choice.iter().map(|x| colleges[*x].applicant_num).collect::<Vec<u32>>();
             let dist = WeightedIndex::new(weight).unwrap();
             while v.len() < select_number{
@@ -222,27 +307,39 @@ impl Student {
             }
             v
         } else {
-            sample(&mut self.rng, size as usize, select_number).into_vec()
+            sample(&mut self.rng, size, select_number).into_vec()
                 .iter()
                 .map(|x| x + offset).collect()
         }
     } 
 
     //入学決定１　志望校合格時に入学 or 入学金納付のみ or パス
-    pub fn admission1(&mut self, _conf: &Config, colleges: &[College], passed_ids: &[Cid]) -> Vec<(usize, (usize, u8))>{
+    // 2026.10.18 資金制約有効時は、入学金を納付する大学（deposit_colleges）以外は保留せず辞退する
+    pub fn admission1(&mut self, conf: &Config, colleges: &[College], passed_ids: &[Cid]) -> Vec<(usize, (usize, u8))>{
         let select_college: Cid;
         match passed_ids.len(){
             0 => Vec::<(usize, (usize, u8))>::new(), //合格大学なし
             _ => { 
-                let mut apply_colleges: Vec::<&College> = self.c_map.iter()
-                    .map(|(key, _)| &colleges[*key])
+                let mut apply_colleges: Vec::<&College> = self.c_map.keys()
+                    .map(|key| &colleges[*key])
                     .collect();
-                apply_colleges.sort_by(|a, b| b.score.cmp(&a.score));
+                // 2026.10.18 同じ偏差値の大学はインデックスの小さい方を選ぶ（HashMapのキー順に依存しないように）
+                apply_colleges.sort_by_key(|x| (Reverse(x.score), x.index));
                 select_college = apply_colleges[0].index;
+                let deposits: Option<Vec<Cid>> = if conf.budget_model {
+                    Some(self.deposit_colleges(conf, colleges, passed_ids))
+                } else {
+                    None
+                };
+                let private_only = matches!(self.pattern, ApplyPattern::PrivateOnly);
     
 
                 //合格大学に対し、私立専願で、最上位の大学が合格なら入学、それ以外には保留の値をもつベクトルを返す
                 let reuslt_vec = passed_ids.iter()
+                    .filter(|cid| match &deposits {
+                        Some(d) => d.contains(cid) || (private_only && **cid == select_college),
+                        None => true,
+                    })
                     .map(|cid|{
                         (*cid, (self.id, 
                                 if *cid == select_college {
@@ -270,6 +367,27 @@ impl Student {
         }
     }
 
+    // 2026.10.18 入学金を納付する私立を決める。偏差値の高い順に、予算内でreserve_max校まで納付する。
+    // 予算が足りない大学は飛ばして次の大学を検討する
+    fn deposit_colleges(&self, conf: &Config, colleges: &[College], passed_ids: &[Cid]) -> Vec<Cid>{
+        let mut candidates: Vec<&College> = passed_ids.iter()
+            .map(|cid| &colleges[*cid])
+            .collect();
+        candidates.sort_by_key(|x| (Reverse(x.score), x.index));
+        let mut budget = self.budget.unwrap_or(u64::MAX);
+        let mut deposits: Vec<Cid> = Vec::new();
+        for c in candidates {
+            if deposits.len() >= conf.reserve_max {
+                break
+            }
+            if c.entrance_fee <= budget {
+                budget -= c.entrance_fee;
+                deposits.push(c.index);
+            }
+        }
+        deposits
+    }
+
     // 国公立合格発表を受けて入学大学を選択．国公立に合格なら入学．
     // 国公立不合格の場合，入学保留中の大学のあれば最高偏差値の私立へ入学すことにしてその大学indexを返す．
     // 戻り値：国公立合格の場合，即入学決定なのでNone,国公立不合格で保留中大学もない場合もNone
@@ -296,7 +414,7 @@ impl Student {
             }
         }
 
-        if reserved_colleges.len() == 0 {
+        if reserved_colleges.is_empty() {
             return None
         }
 
@@ -306,7 +424,7 @@ impl Student {
         }
 
         //保留中の大学から最も偏差値の高い大学に入学
-        reserved_colleges.sort_by(|a, b| b.score.cmp(&a.score));
+        reserved_colleges.sort_by_key(|x| Reverse(x.score));
         self.admission = Some(reserved_colleges[0].index);
         self.admission
     }
@@ -319,68 +437,142 @@ impl Student {
 
         let mut passed_colleges: Vec<&College> = Vec::new();
         for (cid, val) in statuss {
-            if let Some(v) = val {
-                match *v{
-                    //私立第一志望または国公立また保留していた大学に合格している
-                    // Config::R_ADMISSION_1ST | Config::R_ADMISSION_2ND |
-                    // Config::R_ADMISSION_RSV => { // 2021.12.29 追加
-                    //     self.admission = Some(cid);
-                    //     return None //既に決定しているので
-                    // },
-                    //追加合格の大学
-                    Config::R_ENROLL_3RD => {
-                        passed_colleges.push(&colleges[cid]);
-                    },
-                    _ => (),
-                }
+            //私立第一志望または国公立また保留していた大学に合格している
+            // Config::R_ADMISSION_1ST | Config::R_ADMISSION_2ND |
+            // Config::R_ADMISSION_RSV => { // 2021.12.29 追加
+            //     self.admission = Some(cid);
+            //     return None //既に決定しているので
+            // },
+            //追加合格の大学
+            if let Some(&Config::R_ENROLL_3RD) = val {
+                passed_colleges.push(&colleges[cid]);
             }
         }
 
-        if passed_colleges.len() == 0 {
+        if passed_colleges.is_empty() {
             return None
         }
 
-        passed_colleges.sort_by(|a, b| b.score.cmp(&a.score));
+        passed_colleges.sort_by_key(|x| Reverse(x.score));
         self.admission = Some(passed_colleges[0].index);
         self.admission
     }
 
+    // 2026.10.18 指定した日程で指定大学に出願しているか
+    pub fn applied_national(&self, round: usize, cid: Cid) -> bool{
+        self.nationals.iter().any(|(r, c)| *r == round && *c == cid)
+    }
+
     //指定大学の受験時点数（偏差値）を取得
     // pub fn exam_dev(&self, cid: Cid) -> &i32{
     //     self.c_map.get(&cid).unwrap()
     // }
 }
 
+// 2026.10.18 次エポックに再受験する浪人生
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ronin{
+    pub score: i32, //学力向上後の偏差値を1000倍した整数
+    pub pattern: u8, //出願パターン
+    pub pref: u8, //都道府県番号。0は不明
+    pub years: u32, //浪人年数
+    pub budget: Option<u64>, //入学金に充てられる予算（円）。Noneは制約なし
+}
+
+impl Ronin {
+    fn to_record(&self) -> StudentRecord{
+        StudentRecord{
+            score: self.score as f64 / 1000.0,
+            pattern: Some(self.pattern),
+            pref: if self.pref == 0 { None } else { Some(self.pref) },
+            ronin: self.years,
+            budget: self.budget,
+            ..Default::default()
+        }
+    }
+}
+
+// 2026.10.18 入学先のない受験生から、ronin_rateの確率で浪人して次エポックに再受験する者を決める。
+// 偏差値はronin_gainだけ上がる。浪人年数がronin_max_yearsに達した者は再受験しない。
+pub fn carry_over(conf: &Config, students: &mut [Student]) -> Vec<Ronin>{
+    if conf.ronin_rate <= 0.0 {
+        return Vec::new()
+    }
+    students.iter_mut()
+        .filter(|x| x.admission.is_none() && x.ronin < conf.ronin_max_years)
+        .filter_map(|x| if x.rng.gen_bool(conf.ronin_rate) {
+            Some(Ronin{
+                score: x.score + (conf.ronin_gain * 1000.0).round() as i32,
+                pattern: x.pattern.clone() as u8,
+                pref: x.pref,
+                years: x.ronin + 1,
+                budget: x.budget,
+            })
+        } else {
+            None
+        })
+        .collect()
+}
+
+// 2026.10.18 エポックの乱数系列。主系列をepoch+1回long_jumpする
+pub fn epoch_rng(seed: u64, epoch: usize) -> Xoshiro256StarStar{
+    let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+    for _ in 0..=epoch {
+        rng.long_jump();
+    }
+    rng
+}
+
 // シミュレーション結果CSV
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StudentResult{ 
     pub epoch: i32, //エポック数
     pub id: Sid,
     pub score: i32, //偏差値を1000倍した整数
     pub pattern: u8, //出願パターン
+    pub pref: u8, //2026.10.18 都道府県番号。0は不明
+    pub ronin: u32, //2026.10.18 浪人年数。0は現役
     pub college: i32, //2021.12.29 入学した大学の連番．全滅の場合は-1
+    pub fees_paid: u64, //2026.10.18 入学金の支払総額（円）。入学先と入学金納付後に辞退した大学の合計
+    pub fees_forfeited: u64, //2026.10.18 うち入学金納付後に辞退した大学の入学金
+    pub tuition: u64, //2026.10.18 入学先の初年度授業料
+    pub budget: Option<u64>, //2026.10.18 入学金に充てられる予算。空欄は制約なし
     pub result: String, // cid:value_cid:value
 }
 
 // 受験結果マトリクスを１学生１行の形式にしたデバック用受験生入試結果ベクターを作成
 pub fn settle(epoch: i32, students: &[Student], smap: &mut HashMap<Sid,Vec<(Cid, u8)>>, colleges: &[College])  -> Vec<StudentResult>{
    students.par_iter()
-        .map(|s| StudentResult{
-            epoch: epoch,
-            id: s.id,
-            score: s.score,
-            pattern: s.pattern.clone() as u8,
-            college: match s.admission{Some(cid)=>cid as i32, _ => -1},
-            result: if let Some(c_vec) = smap.get(&s.id){
-                        c_vec.iter()
-                            .map(|(cid, status)| 
-                                format!("{}:{}:{}", colleges[*cid].institute, cid, *status) )
-                            .collect::<Vec<_>>()
-                            .join(" ")
-                    } else {//受験せず
-                        "".to_string()
-                    }
+        .map(|s| {
+            let c_vec = smap.get(&s.id);
+            // 2026.10.18 入学金・授業料の支払額
+            let fees_forfeited: u64 = c_vec.map_or(0, |v| v.iter()
+                .filter(|(_, status)| *status == Config::R_DECLINE1_PAID)
+                .map(|(cid, _)| colleges[*cid].entrance_fee)
+                .sum());
+            let (fee, tuition) = s.admission.map_or((0, 0), |cid| (colleges[cid].entrance_fee, colleges[cid].tuition));
+            StudentResult{
+                epoch,
+                id: s.id,
+                score: s.score,
+                pattern: s.pattern.clone() as u8,
+                pref: s.pref,
+                ronin: s.ronin,
+                college: match s.admission{Some(cid)=>cid as i32, _ => -1},
+                fees_paid: fee + fees_forfeited,
+                fees_forfeited,
+                tuition,
+                budget: s.budget,
+                result: if let Some(c_vec) = c_vec{
+                            c_vec.iter()
+                                .map(|(cid, status)| 
+                                    format!("{}:{}:{}", colleges[*cid].institute, cid, *status) )
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        } else {//受験せず
+                            "".to_string()
+                        }
             }
-        )
+        })
         .collect()
-}
\ No newline at end of file
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use toml::Value;
+use toml::value::Table;
+
+use crate::config::{self, Config};
+
+// スイープファイルの予約キー
+//   base = "configS1.toml"   基本設定ファイル（スイープファイルからの相対パス）
+//   [[runs]]                 実行毎の上書きテーブル。labelで出力先フォルダー名を指定
+//   [grid]                   項目名 = [値の配列]。全組み合わせを実行
+// それ以外のトップレベルの項目は全実行共通の上書きになる。
+const BASE_KEY: &str = "base";
+const RUNS_KEY: &str = "runs";
+const GRID_KEY: &str = "grid";
+const LABEL_KEY: &str = "label";
+
+// 展開済みの1実行分
+#[derive(Debug, Clone)]
+pub struct SweepRun{
+    pub label: String,
+    pub table: Table, //上書き適用後の設定
+}
+
+// マニフェスト 1行 = label -> 実効設定
+#[derive(Debug, Serialize)]
+struct ManifestEntry<'a>{
+    label: &'a str,
+    output_dir: &'a str,
+    config: &'a Config,
+}
+
+// runsまたはgridを持つ設定ファイルはスイープファイルとみなす
+pub fn is_sweep(value: &Value) -> bool{
+    match value.as_table(){
+        Some(t) => t.contains_key(RUNS_KEY) || t.contains_key(GRID_KEY),
+        None => false,
+    }
+}
+
+// スイープファイルを展開して実行単位のリストを返す
+pub fn expand(filename: &str, value: Value) -> Result<Vec<SweepRun>>{
+    let mut top = match value {
+        Value::Table(t) => t,
+        _ => return Err(anyhow!("sweep file must be a table")),
+    };
+
+    // 1.基本設定
+    let mut base = match top.remove(BASE_KEY){
+        Some(Value::String(path)) => {
+            let dir = Path::new(filename).parent().unwrap_or_else(|| Path::new(""));
+            config::load_table(&dir.join(path))?
+        },
+        Some(v) => return Err(anyhow!("base must be a file name: {}", v)),
+        None => Table::new(),
+    };
+    let runs = top.remove(RUNS_KEY);
+    let grid = top.remove(GRID_KEY);
+
+    // 2.全実行共通の上書き
+    merge(&mut base, &top);
+
+    // 3.実行毎の上書き。runsがなければ上書きなしの1実行
+    let mut expanded: Vec<SweepRun> = Vec::new();
+    match runs{
+        Some(Value::Array(arr)) => {
+            for (i, v) in arr.into_iter().enumerate(){
+                let mut overrides = match v {
+                    Value::Table(t) => t,
+                    _ => return Err(anyhow!("runs[{}] must be a table", i)),
+                };
+                let label = match overrides.remove(LABEL_KEY){
+                    Some(Value::String(s)) => s,
+                    Some(v) => v.to_string(),
+                    None => format!("run{:02}", i),
+                };
+                let mut table = base.clone();
+                merge(&mut table, &overrides);
+                expanded.push(SweepRun{label, table});
+            }
+        },
+        Some(_) => return Err(anyhow!("runs must be an array of tables")),
+        None => expanded.push(SweepRun{label: String::new(), table: base}),
+    }
+
+    // 4.グリッドの全組み合わせに展開
+    if let Some(grid) = grid {
+        let grid = grid.as_table().ok_or_else(|| anyhow!("grid must be a table"))?;
+        for (key, values) in grid.iter(){
+            let values = values.as_array()
+                .ok_or_else(|| anyhow!("grid.{} must be an array", key))?;
+            expanded = expanded.into_iter()
+                .flat_map(|run| values.iter().map(move |v|{
+                    let mut table = run.table.clone();
+                    table.insert(key.clone(), v.clone());
+                    SweepRun{label: join_label(&run.label, &format!("{}={}", key, label_value(v))), table}
+                }).collect::<Vec<_>>())
+                .collect();
+        }
+    }
+
+    for run in expanded.iter_mut(){
+        if run.label.is_empty(){
+            run.label = "base".to_string();
+        }
+        run.label = sanitize(&run.label);
+    }
+    Ok(expanded)
+}
+
+// スイープ全体の出力先ディレクトリを作成し、その相対パス名を返す。
+pub fn make_sweep_dir(output_dir_base: &str, filename: &str) -> Result<String>{
+    let stem = Path::new(filename).file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "sweep".to_string());
+    let dir = format!("{}/{}_{}", output_dir_base, stem, chrono::Local::now().format("%Y_%m%d_%H%M%S"));
+    fs::create_dir_all(&dir).context("dir cannot create")?;
+    Ok(dir)
+}
+
+// label -> 実効設定のマニフェストをスイープ出力先に保存
+pub fn write_manifest(dir: &str, labels: &[String], confs: &[Config]) -> Result<()>{
+    let entries: Vec<ManifestEntry> = labels.iter().zip(confs.iter())
+        .map(|(label, conf)| ManifestEntry{label, output_dir: &conf.output_dir, config: conf})
+        .collect();
+    let content = serde_json::to_string_pretty(&entries)?;
+    fs::write(format!("{}/manifest.json", dir), content)?;
+    Ok(())
+}
+
+// 上書きテーブルの項目で置き換える
+fn merge(table: &mut Table, overrides: &Table){
+    for (k, v) in overrides.iter(){
+        table.insert(k.clone(), v.clone());
+    }
+}
+
+fn join_label(label: &str, part: &str) -> String{
+    if label.is_empty() { part.to_string() } else { format!("{}_{}", label, part) }
+}
+
+// フォルダー名用の値表記。配列は-で連結
+fn label_value(v: &Value) -> String{
+    match v {
+        Value::String(s) => s.clone(),
+        Value::Array(arr) => arr.iter().map(label_value).collect::<Vec<_>>().join("-"),
+        _ => v.to_string(),
+    }
+}
+
+// フォルダー名に使えない文字を_に置き換える
+fn sanitize(label: &str) -> String{
+    label.chars()
+        .map(|c| if c.is_alphanumeric() || "._-=".contains(c) { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,64 @@
+// 2026.10.18 チェックポイントから再開した実行が、中断しない実行と同じ結果になることを確認する
+mod common;
+
+use anyhow::Result;
+
+use examsim::Simulation;
+use examsim::checkpoint::{Checkpoint, CHECKPOINT_FILE};
+
+// エポック別の大学・受験生集計結果をJSONで記録しながら1エポック進める
+fn step(sim: &mut Simulation, results: &mut Vec<(String, String)>) -> Result<()> {
+    sim.step()?;
+    results.push((serde_json::to_string(sim.college_results())?, serde_json::to_string(sim.student_results())?));
+    Ok(())
+}
+
+#[test]
+fn resume_gives_same_results() -> Result<()> {
+    let conf = common::test_conf("checkpoint")?;
+    let dir = std::env::temp_dir().join(format!("examsim_checkpoint_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    // 中断しない実行
+    let mut sim = Simulation::from_conf(conf.clone())?;
+    let mut expected = Vec::new();
+    while !sim.is_finished() {
+        step(&mut sim, &mut expected)?;
+    }
+    let expected_history = serde_json::to_string(sim.colleges())?;
+
+    // エポックNの終了時点で保存し、読み込んだチェックポイントから再開
+    for n in 1..conf.epochs {
+        let mut sim = Simulation::from_conf(conf.clone())?;
+        let mut results = Vec::new();
+        while sim.epoch() < n {
+            step(&mut sim, &mut results)?;
+        }
+        sim.checkpoint().save(&dir.to_string_lossy())?;
+        drop(sim);
+
+        let cp = Checkpoint::load(&dir.join(CHECKPOINT_FILE))?;
+        let mut sim = Simulation::from_checkpoint(conf.clone(), cp)?;
+        assert_eq!(sim.epoch(), n);
+        while !sim.is_finished() {
+            step(&mut sim, &mut results)?;
+        }
+        assert_eq!(results, expected, "resume from epoch {}", n);
+        assert_eq!(serde_json::to_string(sim.colleges())?, expected_history, "resume from epoch {}", n);
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+    std::fs::remove_file(&conf.initial_college_csv)?;
+    Ok(())
+}
+
+#[test]
+fn reject_other_format() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("examsim_old_checkpoint_{}.bin", std::process::id()));
+    // 形式の番号がない旧形式（先頭はバージョン文字列）
+    std::fs::write(&path, bincode::serialize(&env!("CARGO_PKG_VERSION").to_string())?)?;
+    let err = Checkpoint::load(&path).unwrap_err();
+    std::fs::remove_file(&path)?;
+    assert!(err.to_string().contains("古い形式"), "{}", err);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// 2026.10.18 テスト用の小規模な設定
+use anyhow::Result;
+
+use examsim::config::Config;
+
+// 大学初期値CSV（国立3、公立2、私立15）
+pub const COLLEGES: &str = "\
+cid,name,institute,pref,urban,capa,dev,enroll,over_rate,applicant_num,passed_num,adm_num
+1000,大学0,1,38,都市,600,44.7,150,1.1,750,358,153
+1001,大学1,1,46,地方,400,39.8,100,1.1,500,153,98
+1002,大学2,1,41,地方,1200,51.2,300,1.1,1500,609,291
+1003,大学3,2,11,地方,1200,42.2,300,1.1,2400,365,319
+1004,大学4,2,18,都市,400,59.2,100,1.1,500,221,107
+1005,大学5,3,29,地方,1200,51.2,300,1.21,1500,514,321
+1006,大学6,3,17,都市,600,65.0,150,1.26,450,186,153
+1007,大学7,3,37,地方,1200,65.0,300,1.23,1500,524,322
+1008,大学8,3,18,都市,1200,47.8,300,1.18,1500,625,326
+1009,大学9,3,37,地方,600,65.7,150,1.21,750,368,162
+1010,大学10,3,31,都市,600,40.9,150,1.19,1200,232,145
+1011,大学11,3,19,地方,1200,61.9,300,1.03,900,520,291
+1012,大学12,3,25,都市,1200,50.0,300,1.26,900,595,319
+1013,大学13,3,20,地方,800,58.1,200,1.17,1000,371,219
+1014,大学14,3,19,都市,400,38.2,100,1.18,300,243,109
+1015,大学15,3,25,地方,600,55.1,150,1.21,750,241,164
+1016,大学16,3,33,都市,1200,48.5,300,1.19,2400,625,289
+1017,大学17,3,36,地方,600,45.6,150,1.28,750,370,154
+1018,大学18,3,41,都市,1200,47.5,300,1.3,1500,367,312
+1019,大学19,3,18,地方,800,41.7,200,1.14,1600,478,208
+";
+
+pub fn test_conf(name: &str) -> Result<Config> {
+    let path = std::env::temp_dir().join(format!("examsim_{}_{}.csv", name, std::process::id()));
+    std::fs::write(&path, COLLEGES)?;
+    Ok(Config{
+        initial_college_csv: path.to_string_lossy().to_string(),
+        student_number: vec![5000, 4800, 4600],
+        epochs: 3,
+        start_year: 2020,
+        logging: true, // 受験生別集計結果はログ出力時のみ
+        ronin_rate: 0.3,
+        budget_model: true,
+        ..Config::default()
+    })
+}
@@ -0,0 +1,38 @@
+// 2026.10.18 再現性の確認。スレッド数だけを変えて実行し、大学・受験生の集計結果と履歴が同一であることを確認する
+mod common;
+
+use anyhow::Result;
+use rayon::ThreadPoolBuilder;
+
+use examsim::Simulation;
+use examsim::config::Config;
+
+// 全エポック実行し、エポック別の大学・受験生集計結果と最終の大学エージェント（履歴付き）をJSONで返す
+fn run(conf: &Config, threads: usize) -> Result<(Vec<String>, Vec<String>, String)> {
+    ThreadPoolBuilder::new().num_threads(threads).build()?.install(|| {
+        let mut sim = Simulation::from_conf(conf.clone())?;
+        let mut college_results = Vec::new();
+        let mut student_results = Vec::new();
+        while !sim.is_finished() {
+            sim.step()?;
+            college_results.push(serde_json::to_string(sim.college_results())?);
+            student_results.push(serde_json::to_string(sim.student_results())?);
+        }
+        let history = serde_json::to_string(sim.colleges())?;
+        Ok((college_results, student_results, history))
+    })
+}
+
+#[test]
+fn same_results_for_any_thread_count() -> Result<()> {
+    let conf = common::test_conf("determinism")?;
+    let (colleges1, students1, history1) = run(&conf, 1)?;
+    let (colleges4, students4, history4) = run(&conf, 4)?;
+    std::fs::remove_file(&conf.initial_college_csv)?;
+
+    assert!(students1.iter().all(|s| s != "[]"));
+    assert_eq!(colleges1, colleges4);
+    assert_eq!(students1, students4);
+    assert_eq!(history1, history4);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// 2026.10.18 結果ファイルの書き込み・読み込みの往復。入学者0の大学は入学者偏差値平均がNaNになる
+use std::path::Path;
+use anyhow::Result;
+
+use examsim::college::CollegeResult;
+use examsim::sink::{create_sink, read_results, OutputFormat};
+
+fn records() -> Vec<CollegeResult> {
+    vec![
+        CollegeResult{
+            epoch: 1, index: 0, cid: 1000, name: "大学0".to_string(), institute: 3,
+            dev: 52.5, over_rate: 1.2, apply_count: 300, enroll_1st_count: 240,
+            admissons: 200, new_deviation: 51.25, payments: 210,
+            ..Default::default()
+        },
+        CollegeResult{
+            epoch: 1, index: 1, cid: 1001, name: "大学1".to_string(), institute: 1,
+            dev: 48.0, over_rate: f64::NAN, admissons: 0, new_deviation: f64::NAN,
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn round_trip_with_nan() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("examsim_sink_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let expected = records();
+    for format in [OutputFormat::Csv, OutputFormat::Jsonl, OutputFormat::Bin].iter() {
+        let path = dir.join("college01").with_extension(format.extension());
+        let mut sink = create_sink::<CollegeResult>(*format, &path)?;
+        sink.write_all(&expected)?;
+        sink.finish()?;
+        drop(sink);
+
+        let actual: Vec<CollegeResult> = read_results(Path::new(&path))?;
+        assert_eq!(actual.len(), expected.len(), "{:?}", format);
+        assert!(actual[1].new_deviation.is_nan() && actual[1].over_rate.is_nan(), "{:?}", format);
+        // NaNはJSONでnullになるので、JSON表記で比較する
+        assert_eq!(serde_json::to_string(&actual)?, serde_json::to_string(&expected)?, "{:?}", format);
+    }
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}